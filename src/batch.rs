@@ -0,0 +1,119 @@
+use crate::{Resolver, RlddError, RlddOptions, RlddRexInfo, warning};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{Receiver, SyncSender, sync_channel};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+pub struct BatchScan {
+    rx: Receiver<(PathBuf, Result<RlddRexInfo, RlddError>)>,
+}
+
+impl Iterator for BatchScan {
+    type Item = (PathBuf, Result<RlddRexInfo, RlddError>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.rx.recv().ok()
+    }
+}
+
+pub(crate) fn is_elf_file(path: &Path) -> bool {
+    let mut magic = [0u8; 4];
+    fs::File::open(path)
+        .and_then(|mut f| f.read_exact(&mut magic))
+        .is_ok()
+        && magic == *b"\x7fELF"
+}
+
+fn walk_dir(dir: &Path, tx: &SyncSender<PathBuf>, options: &RlddOptions) -> bool {
+    let entries = match fs::read_dir(dir) {
+        Ok(e) => e,
+        Err(e) => {
+            warning(options, format!("Fail to read dir {:?}: {}", dir, e));
+            return true;
+        }
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        // symlinks are skipped so every real file is analyzed only once
+        let Ok(ft) = entry.file_type() else { continue };
+        if ft.is_dir() {
+            if !walk_dir(&path, tx, options) {
+                return false;
+            }
+        } else if ft.is_file() && is_elf_file(&path) && tx.send(path).is_err() {
+            return false;
+        }
+    }
+    true
+}
+
+impl Resolver {
+    pub fn batch_scan_dir<P: AsRef<Path>>(
+        self: &Arc<Self>,
+        root: P,
+        concurrency: usize,
+    ) -> BatchScan {
+        let workers = concurrency.max(1);
+        let (path_tx, path_rx) = sync_channel::<PathBuf>(workers * 2);
+        let (res_tx, res_rx) = sync_channel(workers);
+        let path_rx = Arc::new(Mutex::new(path_rx));
+
+        let root = root.as_ref().to_path_buf();
+        let options = self.options.clone();
+        thread::spawn(move || walk_dir(&root, &path_tx, &options));
+
+        for _ in 0..workers {
+            let resolver = Arc::clone(self);
+            let path_rx = Arc::clone(&path_rx);
+            let res_tx = res_tx.clone();
+            thread::spawn(move || {
+                loop {
+                    let next = path_rx.lock().unwrap().recv();
+                    let Ok(path) = next else { break };
+                    let info = resolver.rldd_rex(&path);
+                    if res_tx.send((path, info)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+
+        BatchScan { rx: res_rx }
+    }
+
+    // direct DT_NEEDED only, each binary counts once per soname
+    pub fn soname_usage<P: AsRef<Path>>(&self, root: P) -> BTreeMap<String, usize> {
+        let (path_tx, path_rx) = sync_channel::<PathBuf>(64);
+        let root = root.as_ref().to_path_buf();
+        let options = self.options.clone();
+        thread::spawn(move || walk_dir(&root, &path_tx, &options));
+
+        let mut usage = BTreeMap::new();
+        for path in path_rx {
+            let obj = match self.object(&path) {
+                Ok(obj) => obj,
+                Err(e) => {
+                    self.warn(format!("Fail to parser ELF {:?}: {}", path, e));
+                    continue;
+                }
+            };
+            let needed: HashSet<&String> = obj.libraries.iter().collect();
+            for soname in needed {
+                *usage.entry(soname.clone()).or_insert(0) += 1;
+            }
+        }
+        usage
+    }
+}
+
+pub fn batch_scan_dir<P: AsRef<Path>>(root: P, concurrency: usize) -> BatchScan {
+    Arc::new(Resolver::new()).batch_scan_dir(root, concurrency)
+}
+
+pub fn soname_usage<P: AsRef<Path>>(root: P) -> BTreeMap<String, usize> {
+    Resolver::new().soname_usage(root)
+}
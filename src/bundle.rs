@@ -0,0 +1,120 @@
+use crate::batch::is_elf_file;
+use crate::{ExtraDirsPosition, ResolvedLib, Resolver, SearchSource};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// executables live here in AppDir (usr/bin) and Flatpak (files/bin) layouts
+const BIN_DIRS: [&str; 3] = ["usr/bin", "bin", "files/bin"];
+const LIB_DIRS: [&str; 6] = [
+    "usr/lib",
+    "usr/lib64",
+    "lib",
+    "lib64",
+    "files/lib",
+    "files/lib64",
+];
+
+#[derive(Debug, Clone)]
+pub struct BundleBinary {
+    pub path: PathBuf,
+    // resolved from outside the bundle root, the interpreter aside
+    pub escaping: Vec<ResolvedLib>,
+    pub missing: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BundleReport {
+    pub root: PathBuf,
+    pub binaries: Vec<BundleBinary>,
+}
+
+impl BundleReport {
+    pub fn is_self_contained(&self) -> bool {
+        self.binaries
+            .iter()
+            .all(|b| b.escaping.is_empty() && b.missing.is_empty())
+    }
+}
+
+fn bundle_executables(root: &Path) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    for dir in BIN_DIRS {
+        let Ok(entries) = fs::read_dir(root.join(dir)) else {
+            continue;
+        };
+        let mut files: Vec<PathBuf> = entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.is_file() && is_elf_file(p))
+            .collect();
+        files.sort();
+        found.extend(files);
+    }
+
+    // AppRun is either the binary itself or a symlink into the bundle
+    let app_run = root.join("AppRun");
+    if let Ok(target) = fs::canonicalize(&app_run)
+        && target.starts_with(root)
+        && is_elf_file(&target)
+        && !found
+            .iter()
+            .any(|p| fs::canonicalize(p).is_ok_and(|p| p == target))
+    {
+        found.push(app_run);
+    }
+    found
+}
+
+impl Resolver {
+    // Every executable of an AppDir or Flatpak-style tree, resolved with the
+    // bundle's own library dirs searched ahead of the system ones.
+    pub fn analyze_bundle<P: AsRef<Path>>(&self, root: P) -> BundleReport {
+        let root = fs::canonicalize(root.as_ref()).unwrap_or_else(|_| root.as_ref().to_path_buf());
+        let mut options = self.options.clone();
+        options.extra_dirs = LIB_DIRS
+            .iter()
+            .map(|dir| root.join(dir))
+            .filter(|dir| dir.is_dir())
+            .collect();
+        options.extra_dirs_position = ExtraDirsPosition::Prepend;
+        let resolver = Resolver::with_options(options);
+
+        let mut report = BundleReport {
+            root: root.clone(),
+            binaries: Vec::new(),
+        };
+        for path in bundle_executables(&root) {
+            let info = match resolver.rldd_rex(&path) {
+                Ok(info) => info,
+                Err(e) => {
+                    self.warn(format!("Fail to analyze {:?}: {}", path, e));
+                    continue;
+                }
+            };
+            let mut binary = BundleBinary {
+                path,
+                escaping: Vec::new(),
+                missing: Vec::new(),
+            };
+            for (lib, (name, status)) in info.libs.iter().zip(&info.deps) {
+                if !status.is_resolved() {
+                    binary.missing.push(name.clone());
+                    continue;
+                }
+                let Some(lib_path) = lib.real_path.as_ref().or(lib.path.as_ref()) else {
+                    continue;
+                };
+                // the loader always comes from the host
+                if lib.source != Some(SearchSource::Interp) && !lib_path.starts_with(&root) {
+                    binary.escaping.push(lib.clone());
+                }
+            }
+            report.binaries.push(binary);
+        }
+        report
+    }
+}
+
+pub fn analyze_bundle<P: AsRef<Path>>(root: P) -> BundleReport {
+    Resolver::new().analyze_bundle(root)
+}
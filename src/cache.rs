@@ -0,0 +1,31 @@
+use crate::{CachedObject, FileStamp, Resolver};
+use std::fs;
+use std::io;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+
+impl Resolver {
+    pub fn save_cache<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let objects = self.objects.lock().unwrap();
+        let entries: Vec<&CachedObject> = objects.values().collect();
+        let data = serde_json::to_vec(&entries)?;
+        fs::write(path, data)
+    }
+
+    pub fn load_cache<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let data = fs::read(path)?;
+        let entries: Vec<CachedObject> = serde_json::from_slice(&data)?;
+
+        let mut objects = self.objects.lock().unwrap();
+        for entry in entries {
+            // entries whose file changed or vanished since saving are dropped
+            let Ok(meta) = fs::metadata(&entry.path) else {
+                continue;
+            };
+            if FileStamp::of(&meta) == entry.stamp {
+                objects.insert((meta.dev(), meta.ino()), entry);
+            }
+        }
+        Ok(())
+    }
+}
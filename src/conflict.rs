@@ -0,0 +1,149 @@
+use crate::{DepStatus, ResolvedLib};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ConflictSeverity {
+    Major,
+    Patch,
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Conflict {
+    pub base: String,
+    pub paths: Vec<PathBuf>,
+    pub severity: ConflictSeverity,
+    pub winner: PathBuf,
+}
+
+// "libfoo.so.1.2" -> "libfoo"
+pub fn soname_base(name: &str) -> &str {
+    match name.find(".so") {
+        Some(i) => &name[..i],
+        None => name,
+    }
+}
+
+// "libfoo.so.1.2.3" -> [1, 2, 3], None when there is no numeric suffix
+pub fn so_version(name: &str) -> Option<Vec<u32>> {
+    let (_, rest) = name.split_once(".so.")?;
+    rest.split('.').map(|n| n.parse().ok()).collect()
+}
+
+fn real_path(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or(path.to_path_buf())
+}
+
+pub fn conflict_severity(paths: &[PathBuf]) -> ConflictSeverity {
+    let versions: Option<Vec<_>> = paths
+        .iter()
+        .map(|p| so_version(&real_path(p).file_name()?.to_string_lossy()))
+        .collect();
+    let Some(versions) = versions else {
+        return ConflictSeverity::Unknown;
+    };
+    if versions.iter().any(|v| v.first() != versions[0].first()) {
+        ConflictSeverity::Major
+    } else if versions.iter().any(|v| *v != versions[0]) {
+        ConflictSeverity::Patch
+    } else {
+        ConflictSeverity::Unknown
+    }
+}
+
+// Groups every resolution of a library family (including the alternative
+// files other requesting objects would have picked) by soname_base and keeps
+// the groups mapping to more than one real file. The winner is whichever
+// member ld.so loads first; alternates are never loaded since the soname is
+// already satisfied by then.
+pub(crate) fn find_conflicts(
+    libs: &[ResolvedLib],
+    deps: &[(String, DepStatus)],
+    alternates: &[(String, PathBuf)],
+    load_rank: &[usize],
+    loaded: &HashMap<String, PathBuf>,
+) -> Vec<Conflict> {
+    let resolved = libs
+        .iter()
+        .zip(deps)
+        .enumerate()
+        .filter(|(_, (_, (_, status)))| status.is_resolved())
+        .filter_map(|(i, (lib, _))| {
+            let rank = load_rank.get(i).copied().unwrap_or(i);
+            Some((lib.name.as_str(), lib.path.clone()?, rank))
+        });
+    let alternates = alternates
+        .iter()
+        .map(|(n, p)| (n.as_str(), p.clone(), usize::MAX));
+
+    struct Group {
+        base: String,
+        paths: Vec<PathBuf>,
+        reals: Vec<PathBuf>,
+        winner: Option<(usize, PathBuf)>,
+    }
+    let mut groups: Vec<Group> = Vec::new();
+    for (name, path, rank) in resolved.chain(alternates) {
+        let base = soname_base(name);
+        let idx = match groups.iter().position(|g| g.base == base) {
+            Some(i) => i,
+            None => {
+                groups.push(Group {
+                    base: base.to_string(),
+                    paths: Vec::new(),
+                    reals: Vec::new(),
+                    winner: None,
+                });
+                groups.len() - 1
+            }
+        };
+        let group = &mut groups[idx];
+        if group.winner.as_ref().is_none_or(|(r, _)| rank < *r) {
+            let winner = loaded.get(name).unwrap_or(&path);
+            group.winner = Some((rank, winner.clone()));
+        }
+        let real = real_path(&path);
+        if !group.reals.contains(&real) {
+            group.reals.push(real);
+            group.paths.push(path);
+        }
+    }
+
+    groups
+        .into_iter()
+        .filter(|g| g.paths.len() > 1)
+        .map(|g| Conflict {
+            severity: conflict_severity(&g.paths),
+            winner: g.winner.map(|(_, p)| p).unwrap_or_default(),
+            base: g.base,
+            paths: g.paths,
+        })
+        .collect()
+}
+
+const CPP_RUNTIMES: [&str; 2] = ["libstdc++", "libc++"];
+
+// sonames of every C++ standard library implementation in the closure
+pub(crate) fn cpp_runtimes(deps: &[(String, DepStatus)]) -> Vec<String> {
+    let mut found: Vec<String> = Vec::new();
+    for (name, _) in deps {
+        if CPP_RUNTIMES.contains(&soname_base(name)) && !found.contains(name) {
+            found.push(name.clone());
+        }
+    }
+    found
+}
+
+// libstdc++ and libc++ are not ABI compatible, mixing them in one process is
+// a bundling mistake
+pub(crate) fn has_cpp_runtime_conflict(runtimes: &[String]) -> bool {
+    CPP_RUNTIMES
+        .iter()
+        .filter(|rt| runtimes.iter().any(|name| soname_base(name) == **rt))
+        .count()
+        > 1
+}
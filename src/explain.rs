@@ -0,0 +1,220 @@
+use crate::{DepStatus, RlddRexInfo, SearchSource, rldd_rex};
+use std::fmt::{self, Write};
+use std::path::Path;
+
+impl RlddRexInfo {
+    pub fn explain(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "ELF: {:?} {:?} {:?}",
+            self.arch, self.machine, self.elf_type
+        );
+        if self.executable_stack {
+            let _ = writeln!(out, "WARNING: executable stack");
+        }
+
+        let _ = writeln!(out, "\nSearch path:");
+        for (i, dir) in self.search_dirs.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "  {}. {} [{:?}]",
+                i + 1,
+                dir.path.display(),
+                dir.source
+            );
+        }
+
+        let _ = writeln!(out, "\nDependencies:");
+        for (lib, (_, status)) in self.libs.iter().zip(&self.deps) {
+            let _ = write!(out, "  {} => ", lib.name);
+            match (&lib.path, status) {
+                (None, DepStatus::Assumed) => {
+                    let _ = write!(out, "assumed present on the target system");
+                }
+                (None, _) => {
+                    let _ = write!(
+                        out,
+                        "not found (checked {} search dirs plus the RPATH/RUNPATH of the requesting object)",
+                        self.search_dirs.len()
+                    );
+                }
+                (Some(path), DepStatus::ArchMismatch) => {
+                    let _ = write!(
+                        out,
+                        "arch mismatch: {} is not {:?}",
+                        path.display(),
+                        self.arch
+                    );
+                }
+                (Some(path), DepStatus::MachineMismatch) => {
+                    let _ = write!(
+                        out,
+                        "machine mismatch: {} is not {:?}",
+                        path.display(),
+                        self.machine
+                    );
+                }
+                (Some(path), DepStatus::PermissionDenied) => {
+                    let _ = write!(out, "{} exists but is not readable", path.display());
+                }
+                (Some(path), DepStatus::ParseError) => {
+                    let _ = write!(out, "{} exists but is not a parseable ELF", path.display());
+                }
+                (Some(path), DepStatus::LinkerScript) => {
+                    let _ = write!(
+                        out,
+                        "{} is a GNU ld script that could not be followed",
+                        path.display()
+                    );
+                }
+                (Some(path), _) => {
+                    let _ = write!(out, "{}", path.display());
+                    if let Some(real) = lib.real_path.as_ref().filter(|real| *real != path) {
+                        let _ = write!(out, " -> {}", real.display());
+                    }
+                    if let Some(soname) = lib.soname.as_ref().filter(|s| **s != lib.name) {
+                        let _ = write!(out, " [SONAME {}]", soname);
+                    }
+                    if let Some(script) = &lib.script {
+                        let _ = write!(out, " via linker script {}", script.display());
+                    }
+                    if let (Some(dir), Some(source)) = (&lib.dir, lib.source) {
+                        let _ = write!(out, " (found in {} [{:?}]", dir.display(), source);
+                    } else {
+                        let _ = write!(out, " (");
+                    }
+                    let _ = write!(out, ", depth {})", lib.depth);
+                    if lib.is_heuristic_only() {
+                        let _ = write!(out, " WARNING: the loader does not search this dir");
+                    }
+                    if let Some(kind) = lib.kind.filter(|k| !k.is_shared()) {
+                        let _ = write!(out, " WARNING: not a shared ELF ({:?})", kind);
+                    }
+                    if lib.executable_stack {
+                        let _ = write!(out, " WARNING: executable stack");
+                    }
+                }
+            }
+            out.push('\n');
+        }
+
+        if !self.warnings.is_empty() {
+            let _ = writeln!(out, "\nWarnings:");
+            for w in &self.warnings {
+                let _ = writeln!(out, "  {}", w);
+            }
+        }
+        out
+    }
+}
+
+impl RlddRexInfo {
+    // Display with the unresolved entries in red, for terminals
+    pub fn format_colored(&self) -> String {
+        let mut out = String::new();
+        let _ = self.write_ldd(&mut out, true);
+        out
+    }
+
+    fn write_ldd(&self, out: &mut impl Write, color: bool) -> fmt::Result {
+        writeln!(
+            out,
+            "{:?} {:?} ({:?})",
+            self.arch, self.machine, self.elf_type
+        )?;
+        for (name, status) in &self.deps {
+            match status {
+                DepStatus::Found(_) | DepStatus::Assumed => {
+                    writeln!(out, "\t{} => {}", name, status)?
+                }
+                _ if color => writeln!(out, "\t{} => \x1b[1;31m{}\x1b[0m", name, status)?,
+                _ => writeln!(out, "\t{} => {}", name, status)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+// ldd-style: a header line, then `name => path` or the status per dependency
+impl fmt::Display for RlddRexInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_ldd(f, false)
+    }
+}
+
+pub fn to_summary_line<P: AsRef<Path>>(path: P, info: &RlddRexInfo) -> String {
+    let resolved = info
+        .deps
+        .iter()
+        .filter(|(_, status)| status.is_resolved())
+        .count();
+    // anything that would not load, not only NotFound
+    let missing = info.deps.len() - resolved;
+    format!(
+        "{}\t{:?}\t{:?}\t{}/{}\t{}",
+        path.as_ref().display(),
+        info.arch,
+        info.elf_type,
+        resolved,
+        info.deps.len(),
+        missing
+    )
+}
+
+fn source_name(source: SearchSource) -> &'static str {
+    match source {
+        SearchSource::Interp => "interp",
+        SearchSource::Rpath => "rpath",
+        SearchSource::LdLibraryPath => "ld_library_path",
+        SearchSource::Runpath => "runpath",
+        SearchSource::LdSoConf => "ld_so_conf",
+        SearchSource::System => "default",
+        SearchSource::BinDir => "bin_dir",
+        SearchSource::Virtual => "virtual",
+        SearchSource::Direct => "direct",
+        SearchSource::Extra => "extra",
+    }
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+pub fn to_table(info: &RlddRexInfo) -> String {
+    let mut out = String::from("index,soname,status,path,source,depth\n");
+    for (i, (lib, (name, status))) in info.libs.iter().zip(&info.deps).enumerate() {
+        let status = if status.is_resolved() && lib.path.is_some() {
+            "found".to_string()
+        } else {
+            status.to_string()
+        };
+        let path = lib
+            .path
+            .as_ref()
+            .map(|p| p.display().to_string())
+            .unwrap_or_default();
+        let _ = writeln!(
+            out,
+            "{},{},{},{},{},{}",
+            i + 1,
+            csv_field(name),
+            status,
+            csv_field(&path),
+            lib.source.map(source_name).unwrap_or(""),
+            lib.depth
+        );
+    }
+    out
+}
+
+pub fn explain<P: AsRef<Path> + std::fmt::Debug>(path: P) -> String {
+    match rldd_rex(&path) {
+        Ok(info) => format!("{}\n{}", path.as_ref().display(), info.explain()),
+        Err(e) => format!("{}: {}\n", path.as_ref().display(), e),
+    }
+}
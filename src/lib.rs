@@ -1,24 +1,62 @@
 use glob::glob;
 use goblin::elf::Elf;
+use goblin::elf::dynamic::{
+    DF_1_NODEFLIB, DF_1_NOW, DF_1_PIE, DF_BIND_NOW, DF_STATIC_TLS, DT_BIND_NOW, DT_GNU_HASH,
+    DT_HASH,
+};
 use goblin::elf::header::*;
+use goblin::elf::program_header::{
+    PF_W, PF_X, PT_DYNAMIC, PT_GNU_RELRO, PT_GNU_STACK, PT_INTERP, PT_LOAD, ProgramHeader,
+};
+use goblin::elf::section_header::SHN_UNDEF;
+use goblin::elf::sym::{STB_GLOBAL, STB_WEAK};
 use memmap2::Mmap;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::fs;
 use std::fs::File;
 use std::io;
 use std::os::unix::fs::MetadataExt;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+mod batch;
+mod bundle;
+#[cfg(feature = "serde")]
+mod cache;
+mod conflict;
+mod explain;
+mod parsed;
+#[cfg(feature = "serde")]
+mod serde_deps;
+mod topo;
+mod tree;
+mod weight;
+
+pub use batch::{BatchScan, batch_scan_dir, soname_usage};
+pub use bundle::{BundleBinary, BundleReport, analyze_bundle};
+pub use conflict::{Conflict, ConflictSeverity, conflict_severity, so_version, soname_base};
+pub use explain::{explain, to_summary_line, to_table};
+pub use parsed::ParsedBinary;
+pub use topo::topo_sorted_closure;
+pub use tree::{DepNode, rldd_rex_tree};
+pub use weight::{SHARED_BUCKET, WeightStats, weight_report};
 
 const MAX_DEPTH: usize = 512;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+// not exported by goblin yet
+const EM_LOONGARCH: u16 = 258;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ElfArch {
     Elf32,
     Elf64,
     Unknown,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ElfMachine {
     X86,
     X86_64,
@@ -26,33 +64,481 @@ pub enum ElfMachine {
     Arm64,
     Mips,
     PowerPC,
+    RiscV32,
+    RiscV64,
+    S390x,
+    LoongArch64,
     Unknown,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ElfType {
     Static,
     Dynamic,
     Pie,
+    Relocatable,
     Invalid,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LibKind {
+    Shared,
+    Static,
+    LinkerScript,
+    Archive,
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SearchSource {
+    Interp,
+    Rpath,
+    LdLibraryPath,
+    Runpath,
+    LdSoConf,
+    System,
+    BinDir,
+    Virtual,
+    Direct,
+    Extra,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LibcFamily {
+    Glibc,
+    Musl,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PackerKind {
+    Upx,
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Relro {
+    #[default]
+    None,
+    Partial,
+    Full,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HashStyle {
+    #[default]
+    None,
+    Sysv,
+    Gnu,
+    Both,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DedupScope {
+    #[default]
+    PerRoot,
+    Shared,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DepOrder {
+    #[default]
+    Traversal,
+    LoadOrder,
+}
+
+// Where LD_LIBRARY_PATH sits among an object's own entries. Modern glibc
+// searches it after RPATH and before RUNPATH; AfterRpathOnly moves it behind
+// RUNPATH as well, so paths baked into the binary always win.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LdLibraryPathPriority {
+    #[default]
+    BeforeRunpath,
+    AfterRpathOnly,
+}
+
+// Where caller-supplied dirs go relative to ld.so.conf and the default dirs.
+// RPATH, RUNPATH and LD_LIBRARY_PATH are searched first either way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExtraDirsPosition {
+    Prepend,
+    #[default]
+    Append,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DepSort {
+    #[default]
+    None,
+    BySoname,
+    ByPath,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SearchDir {
+    pub path: PathBuf,
+    pub source: SearchSource,
+}
+
+// What went into the search, enough to rerun the same analysis
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SearchConfig {
+    pub ld_library_path: Option<String>,
+    pub ld_so_conf: bool,
+    pub extra_ld_conf_files: Vec<PathBuf>,
+    pub musl_path_file: Option<PathBuf>,
+    pub default_dirs: bool,
+    pub bin_dir_heuristic: bool,
+    pub dir_contents: bool,
+    pub cache_dir_listings: bool,
+    pub sysroot: Option<PathBuf>,
+    pub base_dir: Option<PathBuf>,
+    pub extra_dirs: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ResolvedLib {
+    pub name: String,
+    pub path: Option<PathBuf>,
+    pub kind: Option<LibKind>,
+    pub script: Option<PathBuf>,
+    pub depth: usize,
+    pub dir: Option<PathBuf>,
+    pub source: Option<SearchSource>,
+    pub executable_stack: bool,
+    pub uses_static_tls: bool,
+    pub hash_style: HashStyle,
+    pub dynsym_count: usize,
+    // DT_SONAME of the file, which need not match the name it was found by
+    pub soname: Option<String>,
+    pub attempted: Vec<PathBuf>,
+    // path with every symlink resolved, `path` keeps the name that matched
+    pub real_path: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DepStatus {
+    Found(PathBuf),
+    NotFound,
+    ArchMismatch,
+    MachineMismatch,
+    LinkerScript,
+    PermissionDenied,
+    // present but not an ELF object the loader could map
+    ParseError,
+    Assumed,
+}
+
+#[derive(Debug, Clone)]
+pub struct DepEvent {
+    pub name: String,
+    pub status: DepStatus,
+    pub path: Option<PathBuf>,
+    pub depth: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    pub message: String,
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+type HookFn<E> = dyn FnMut(&E) + Send;
+
+pub struct Hook<E>(Arc<Mutex<HookFn<E>>>);
+
+impl<E> Hook<E> {
+    pub fn new(f: impl FnMut(&E) + Send + 'static) -> Self {
+        Hook(Arc::new(Mutex::new(f)))
+    }
+
+    fn call(&self, event: &E) {
+        (self.0.lock().unwrap())(event)
+    }
+}
+
+impl<E> Clone for Hook<E> {
+    fn clone(&self) -> Self {
+        Hook(self.0.clone())
+    }
+}
+
+impl<E> std::fmt::Debug for Hook<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Hook")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RlddOptions {
+    pub follow_ld_scripts: bool,
+    pub on_dep: Option<Hook<DepEvent>>,
+    // takes over diagnostics that would otherwise go to stderr
+    pub on_warning: Option<Hook<Warning>>,
+    pub name_arch_suffix: bool,
+    pub resolve_symbols: bool,
+    pub validate_symbols: bool,
+    // levels of DT_NEEDED to follow, 1 lists only the root's own entries
+    pub max_depth: usize,
+    pub debug_search_dirs: Vec<PathBuf>,
+    pub dedup_scope: DedupScope,
+    pub ld_path_separator: char,
+    pub order: DepOrder,
+    pub sort: DepSort,
+    pub canonicalize_root: bool,
+    pub dir_contents: Option<HashMap<PathBuf, Vec<String>>>,
+    pub assume_present: HashSet<String>,
+    pub force_libc: Option<LibcFamily>,
+    pub cache_dir_listings: bool,
+    pub record_attempts: bool,
+    pub search_libexec: bool,
+    pub virtual_libs: HashMap<String, PathBuf>,
+    pub extra_ld_conf_files: Vec<PathBuf>,
+    pub fail_fast: bool,
+    pub base_dir: Option<PathBuf>,
+    pub sysroot: Option<PathBuf>,
+    pub extra_dirs: Vec<PathBuf>,
+    pub extra_dirs_position: ExtraDirsPosition,
+    pub ld_library_path_priority: LdLibraryPathPriority,
+    // only has an effect with the `parallel` feature
+    pub parallel: bool,
+}
+
+impl Default for RlddOptions {
+    fn default() -> Self {
+        RlddOptions {
+            follow_ld_scripts: true,
+            on_dep: None,
+            on_warning: None,
+            name_arch_suffix: false,
+            resolve_symbols: false,
+            validate_symbols: false,
+            max_depth: MAX_DEPTH,
+            debug_search_dirs: Vec::new(),
+            dedup_scope: DedupScope::PerRoot,
+            ld_path_separator: ':',
+            order: DepOrder::Traversal,
+            sort: DepSort::None,
+            canonicalize_root: true,
+            dir_contents: None,
+            assume_present: HashSet::new(),
+            force_libc: None,
+            cache_dir_listings: false,
+            record_attempts: false,
+            search_libexec: false,
+            virtual_libs: HashMap::new(),
+            extra_ld_conf_files: Vec::new(),
+            fail_fast: false,
+            base_dir: None,
+            sysroot: None,
+            extra_dirs: Vec::new(),
+            extra_dirs_position: ExtraDirsPosition::Append,
+            ld_library_path_priority: LdLibraryPathPriority::BeforeRunpath,
+            parallel: cfg!(feature = "parallel"),
+        }
+    }
+}
+
+impl RlddOptions {
+    // what-if analysis: treat `name` as available at `path` without it existing on disk
+    pub fn add_virtual_lib<P: AsRef<Path>>(mut self, name: &str, path: P) -> Self {
+        self.virtual_libs
+            .insert(name.to_string(), path.as_ref().to_path_buf());
+        self
+    }
+}
+
+#[derive(Debug)]
+pub enum RlddError {
+    // the file could not be opened or mapped
+    Io(io::Error),
+    // no ELF magic at the start of the file
+    NotElf,
+    ParseFailed(String),
+    InconsistentHeader(String),
+}
+
+impl std::fmt::Display for RlddError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RlddError::Io(e) => write!(f, "{}", e),
+            RlddError::NotElf => f.write_str("not an ELF file"),
+            RlddError::ParseFailed(msg) => write!(f, "malformed ELF: {}", msg),
+            RlddError::InconsistentHeader(msg) => write!(f, "inconsistent ELF header: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for RlddError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RlddError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for RlddError {
+    fn from(e: io::Error) -> Self {
+        RlddError::Io(e)
+    }
+}
+
+impl From<RlddError> for io::Error {
+    fn from(e: RlddError) -> Self {
+        match e {
+            RlddError::Io(e) => e,
+            e => io::Error::new(io::ErrorKind::InvalidData, e),
+        }
+    }
+}
+
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RlddRexInfo {
     pub arch: ElfArch,
+    pub machine: ElfMachine,
     pub elf_type: ElfType,
-    pub deps: Vec<(String, String)>,
+    #[cfg_attr(feature = "serde", serde(with = "serde_deps"))]
+    pub deps: Vec<(String, DepStatus)>,
+    pub libs: Vec<ResolvedLib>,
+    // the root's own DT_NEEDED entries, as readelf -d lists them
+    pub direct_deps: Vec<String>,
+    pub warnings: Vec<String>,
+    pub search_dirs: Vec<SearchDir>,
+    pub secure_exec: bool,
+    pub ignored_dirs: Vec<SearchDir>,
+    pub symbol_providers: Vec<(String, String)>,
+    // filled with validate_symbols: undefined dynamic symbols of the root no
+    // resolved library exports
+    pub unresolved_symbols: Vec<String>,
+    pub debug_link: Option<String>,
+    pub debug_file: Option<PathBuf>,
+    pub undefined_symbols: Vec<String>,
+    pub executable_stack: bool,
+    pub usrmerge: bool,
+    pub conflicts: Vec<Conflict>,
+    pub relro: Relro,
+    pub relro_size: u64,
+    pub packed: Option<PackerKind>,
+    pub cpp_runtimes: Vec<String>,
+    pub cpp_runtime_conflict: bool,
+    pub cpu_baseline: Option<String>,
+    pub libc_version: Option<String>,
+    // per library, the symbol versions the root needs from it (.gnu.version_r)
+    pub version_requirements: BTreeMap<String, Vec<String>>,
+    pub failed_at: Option<String>,
+    pub search_config_summary: SearchConfig,
+    pub duplicate_needed: Vec<String>,
+    pub invalid_rpath_dirs: Vec<PathBuf>,
+    // each DT_NEEDED cycle as the chain of names from a library back to itself
+    pub cycles: Vec<Vec<String>>,
+    pub hash_style: HashStyle,
+    pub dynsym_count: usize,
 }
 
-#[cfg(feature = "enable_ld_library_path")]
-fn is_same_arch(arch: ElfArch, sub_elf: &Elf) -> bool {
+fn is_same_arch(arch: ElfArch, sub_obj: &ElfObject) -> bool {
     match arch {
-        ElfArch::Elf32 => !sub_elf.is_64,
-        ElfArch::Elf64 => sub_elf.is_64,
+        ElfArch::Elf32 => !sub_obj.is_64,
+        ElfArch::Elf64 => sub_obj.is_64,
         ElfArch::Unknown => true, // fallback
     }
 }
 
+impl LibKind {
+    pub fn is_shared(&self) -> bool {
+        *self == LibKind::Shared
+    }
+    pub fn is_linker_script(&self) -> bool {
+        *self == LibKind::LinkerScript
+    }
+}
+
+impl DepStatus {
+    // Assumed counts as resolved, the library is expected on the target
+    pub fn is_resolved(&self) -> bool {
+        matches!(self, DepStatus::Found(_) | DepStatus::Assumed)
+    }
+}
+
+impl std::fmt::Display for DepStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DepStatus::Found(path) => write!(f, "{}", path.display()),
+            DepStatus::NotFound => write!(f, "not found"),
+            DepStatus::ArchMismatch => write!(f, "arch mismatch"),
+            DepStatus::MachineMismatch => write!(f, "machine mismatch"),
+            DepStatus::LinkerScript => write!(f, "linker script"),
+            DepStatus::PermissionDenied => write!(f, "permission denied"),
+            DepStatus::ParseError => write!(f, "parse error"),
+            DepStatus::Assumed => write!(f, "assumed"),
+        }
+    }
+}
+
+impl ResolvedLib {
+    // extra_lib_dirs_for_bin guesses where bundled libraries live, ld.so
+    // never searches there without an RPATH/RUNPATH saying so
+    pub fn is_heuristic_only(&self) -> bool {
+        self.source == Some(SearchSource::BinDir)
+    }
+}
+
+impl RlddRexInfo {
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &DepStatus)> {
+        self.deps
+            .iter()
+            .map(|(name, status)| (name.as_str(), status))
+    }
+
+    // everything the loader would fail on, mismatches included
+    pub fn missing(&self) -> impl Iterator<Item = &str> {
+        self.iter()
+            .filter(|(_, status)| !status.is_resolved())
+            .map(|(name, _)| name)
+    }
+
+    pub fn resolved(&self) -> impl Iterator<Item = (&str, &Path)> {
+        self.iter().filter_map(|(name, status)| match status {
+            DepStatus::Found(path) => Some((name, path.as_path())),
+            _ => None,
+        })
+    }
+
+    pub fn used_dirs(&self) -> Vec<PathBuf> {
+        let mut seen = HashSet::new();
+        self.libs
+            .iter()
+            .zip(&self.deps)
+            .filter(|(_, (_, status))| status.is_resolved())
+            .filter_map(|(lib, _)| lib.dir.clone())
+            .filter(|dir| seen.insert(dir.clone()))
+            .collect()
+    }
+
+    pub fn closure_has_exec_stack(&self) -> bool {
+        self.executable_stack
+            || self
+                .libs
+                .iter()
+                .zip(&self.deps)
+                .any(|(lib, (_, status))| lib.executable_stack && status.is_resolved())
+    }
+}
+
 impl ElfType {
     pub fn is_static(&self) -> bool {
         *self == ElfType::Static
@@ -63,11 +549,22 @@ impl ElfType {
     pub fn is_pie(&self) -> bool {
         *self == ElfType::Pie
     }
+    pub fn is_relocatable(&self) -> bool {
+        *self == ElfType::Relocatable
+    }
     pub fn is_valid(&self) -> bool {
         *self != ElfType::Invalid
     }
 }
 
+// ET_DYN alone doesn't make an executable: libc.so.6 carries PT_INTERP too.
+// DF_1_PIE is authoritative, older linkers leave it out, then an interpreter
+// without a DT_SONAME is the best hint.
+fn is_pie(elf: &Elf) -> bool {
+    let flags_1 = elf.dynamic.as_ref().map_or(0, |d| d.info.flags_1);
+    flags_1 & DF_1_PIE != 0 || (elf.interpreter.is_some() && elf.soname.is_none())
+}
+
 fn get_elf_type(elf: &Elf) -> ElfType {
     match elf.header.e_type {
         ET_EXEC => {
@@ -78,34 +575,88 @@ fn get_elf_type(elf: &Elf) -> ElfType {
             }
         }
         ET_DYN => {
-            if elf.interpreter.is_some() {
+            if is_pie(elf) {
                 ElfType::Pie
             } else {
                 ElfType::Dynamic
             }
         }
+        ET_REL => ElfType::Relocatable,
         _ => ElfType::Invalid, // ET_CORE
     }
 }
 
-fn machine_from_e_machine(e_machine: u16) -> ElfMachine {
+// Rejects class/data/machine combinations no real toolchain produces.
+// ELFCLASS32 with EM_X86_64 is fine, that's x32.
+fn check_header(elf: &Elf) -> Result<(), RlddError> {
+    let (is_64, le) = (elf.is_64, elf.little_endian);
+    let (class_ok, data_ok) = match elf.header.e_machine {
+        EM_386 => (!is_64, le),
+        EM_X86_64 | EM_RISCV | EM_LOONGARCH => (true, le),
+        EM_ARM | EM_PPC => (!is_64, true),
+        EM_PPC64 => (is_64, true),
+        EM_SPARC | EM_68K => (!is_64, !le),
+        EM_SPARCV9 => (is_64, !le),
+        EM_S390 => (true, !le),
+        _ => (true, true),
+    };
+    if class_ok && data_ok {
+        return Ok(());
+    }
+    Err(RlddError::InconsistentHeader(format!(
+        "{} {} file for machine {}",
+        ["ELFCLASS32", "ELFCLASS64"][is_64 as usize],
+        ["big endian", "little endian"][le as usize],
+        machine_to_str(elf.header.e_machine)
+    )))
+}
+
+fn machine_from_e_machine(e_machine: u16, arch: ElfArch) -> ElfMachine {
     match e_machine {
         EM_386 => ElfMachine::X86,
         EM_X86_64 => ElfMachine::X86_64,
         EM_ARM => ElfMachine::Arm32,
         EM_AARCH64 => ElfMachine::Arm64,
         EM_MIPS => ElfMachine::Mips,
-        EM_PPC => ElfMachine::PowerPC,
+        EM_PPC | EM_PPC64 => ElfMachine::PowerPC,
+        EM_RISCV => match arch {
+            ElfArch::Elf32 => ElfMachine::RiscV32,
+            _ => ElfMachine::RiscV64,
+        },
+        EM_S390 => ElfMachine::S390x,
+        EM_LOONGARCH if arch == ElfArch::Elf64 => ElfMachine::LoongArch64,
         _ => ElfMachine::Unknown,
     }
 }
 
+// Absolute paths of the inspected system live under the sysroot
+fn in_sysroot(sysroot: Option<&Path>, path: &Path) -> PathBuf {
+    match sysroot {
+        Some(root) if path.is_absolute() => root.join(path.strip_prefix("/").unwrap_or(path)),
+        _ => path.to_path_buf(),
+    }
+}
+
+fn warning(options: &RlddOptions, message: String) {
+    match &options.on_warning {
+        Some(hook) => hook.call(&Warning { message }),
+        None => eprintln!("{}", message),
+    }
+}
+
 #[cfg(any(target_os = "linux", target_os = "solaris"))]
-fn read_ld_so_conf() -> io::Result<Vec<PathBuf>> {
+fn read_ld_so_conf(options: &RlddOptions) -> io::Result<Vec<PathBuf>> {
     let mut collected = Vec::new();
     let mut seen = HashSet::new();
+    let sysroot = options.sysroot.as_deref();
 
-    fn process_file(path: &Path, collected: &mut Vec<PathBuf>, seen: &mut HashSet<PathBuf>) {
+    fn process_file(
+        path: &Path,
+        options: &RlddOptions,
+        collected: &mut Vec<PathBuf>,
+        seen: &mut HashSet<PathBuf>,
+    ) {
+        let sysroot = options.sysroot.as_deref();
         if let Ok(content) = fs::read_to_string(path) {
             for line in content
                 .lines()
@@ -113,29 +664,33 @@ fn read_ld_so_conf() -> io::Result<Vec<PathBuf>> {
                 .filter(|l| !l.is_empty() && !l.starts_with('#'))
             {
                 if let Some(rest) = line.strip_prefix("include") {
-                    let pattern = rest.trim();
-                    if let Ok(entries) = glob(pattern) {
+                    let pattern = in_sysroot(sysroot, Path::new(rest.trim()));
+                    let pattern = pattern.to_string_lossy();
+                    if let Ok(entries) = glob(&pattern) {
                         for entry in entries.flatten().filter(|e| e.is_file()) {
-                            process_file(&entry, collected, seen);
+                            process_file(&entry, options, collected, seen);
                         }
                     } else {
-                        eprintln!("Glob error '{}'", pattern);
+                        warning(options, format!("Glob error '{}'", pattern));
                     }
                 } else {
-                    let dir = PathBuf::from(line);
+                    let dir = in_sysroot(sysroot, Path::new(line));
                     if dir.exists() && dir.is_dir() && seen.insert(dir.clone()) {
                         collected.push(dir);
                     }
                 }
             }
         } else {
-            eprintln!("Fail to read {:?}", path);
+            warning(options, format!("Fail to read {:?}", path));
         }
     }
 
-    let base = Path::new("/etc/ld.so.conf");
+    let base = in_sysroot(sysroot, Path::new("/etc/ld.so.conf"));
     if base.exists() {
-        process_file(base, &mut collected, &mut seen);
+        process_file(&base, options, &mut collected, &mut seen);
+    }
+    for extra in &options.extra_ld_conf_files {
+        process_file(extra, options, &mut collected, &mut seen);
     }
 
     Ok(collected)
@@ -209,6 +764,28 @@ fn default_dirs_for_arch_and_machine(elf_arch: ElfArch, machine: ElfMachine) ->
             ],
             _ => vec![],
         },
+        ElfMachine::RiscV32 => vec![
+            PathBuf::from("/lib/riscv32-linux-gnu"),
+            PathBuf::from("/usr/lib/riscv32-linux-gnu"),
+        ],
+        ElfMachine::RiscV64 => vec![
+            PathBuf::from("/lib/riscv64-linux-gnu"),
+            PathBuf::from("/usr/lib/riscv64-linux-gnu"),
+        ],
+        ElfMachine::S390x => match elf_arch {
+            ElfArch::Elf64 => vec![
+                PathBuf::from("/lib/s390x-linux-gnu"),
+                PathBuf::from("/usr/lib/s390x-linux-gnu"),
+            ],
+            _ => vec![],
+        },
+        ElfMachine::LoongArch64 => match elf_arch {
+            ElfArch::Elf64 => vec![
+                PathBuf::from("/lib/loongarch64-linux-gnu"),
+                PathBuf::from("/usr/lib/loongarch64-linux-gnu"),
+            ],
+            _ => vec![],
+        },
         ElfMachine::Unknown => vec![],
     };
 
@@ -216,256 +793,2492 @@ fn default_dirs_for_arch_and_machine(elf_arch: ElfArch, machine: ElfMachine) ->
     dirs
 }
 
-fn build_search_dirs(elf: &Elf, arch: ElfArch, machine: ElfMachine) -> Vec<PathBuf> {
-    let mut dirs = vec![
-        PathBuf::from("/lib"),
-        PathBuf::from("/usr/lib"),
-        PathBuf::from("/usr/local/lib"),
-        PathBuf::from("/usr/libexec"),
-        PathBuf::from("/libexec"),
-    ];
-
-    #[cfg(feature = "enable_ld_library_path")]
-    if let Ok(ld_path) = std::env::var("LD_LIBRARY_PATH") {
-        for p in ld_path.split(':') {
-            let seg = if p.is_empty() { "." } else { p };
-            dirs.push(PathBuf::from(seg));
-        }
-    }
+const EF_RISCV_FLOAT_ABI: u32 = 0x6;
 
-    let is_musl = if let Some(interp) = elf.interpreter {
-        interp.contains("musl")
-    } else {
-        false
+// multilib layouts keep each float ABI in its own subdir, e.g. /usr/lib64/lp64d
+fn riscv_abi_dirs(machine: ElfMachine, e_flags: u32) -> Vec<PathBuf> {
+    let float = match e_flags & EF_RISCV_FLOAT_ABI {
+        0x0 => "",
+        0x2 => "f",
+        0x4 => "d",
+        _ => "q",
     };
 
-    if is_musl {
-        let musl_conf = Path::new("/etc/ld-musl-x86_64.path");
-        if musl_conf.exists() {
-            if let Ok(content) = fs::read_to_string(musl_conf) {
-                for line in content.lines() {
-                    let trim = line.trim();
-                    if !trim.is_empty() {
-                        dirs.push(PathBuf::from(trim));
-                    }
-                }
-            }
-        }
-    } else {
-        #[cfg(any(target_os = "linux", target_os = "solaris"))]
-        if let Err(e) = read_ld_so_conf().map(|ld_dirs| dirs.extend(ld_dirs)) {
-            eprintln!("Error reading ld.so.conf: {}", e);
-        }
-        dirs.extend(default_dirs_for_arch_and_machine(arch, machine));
-    }
-
-    let mut uniq = Vec::new();
-    let mut seen = HashSet::new();
-    for d in dirs {
-        let path = d.canonicalize().unwrap_or(d);
-        if seen.insert(path.clone()) {
-            uniq.push(path);
-        }
-    }
+    let (libdir, abi) = match machine {
+        ElfMachine::RiscV32 => ("lib32", format!("ilp32{}", float)),
+        ElfMachine::RiscV64 => ("lib64", format!("lp64{}", float)),
+        _ => return vec![],
+    };
 
-    uniq
+    vec![
+        PathBuf::from(format!("/{}/{}", libdir, abi)),
+        PathBuf::from(format!("/usr/{}/{}", libdir, abi)),
+    ]
 }
 
-fn find_library(lib: &str, search_dirs: &[PathBuf], paths: &[PathBuf]) -> Option<PathBuf> {
-    let mut dirs = search_dirs.to_vec();
-    dirs.extend(paths.iter().map(PathBuf::from));
-
-    for dir in dirs {
-        let candidate = dir.join(lib);
-        if candidate.exists() {
-            return Some(candidate);
-        }
+// glibc-hwcaps subdirectories, most advanced first. The loader skips the
+// levels the running CPU lacks, without a CPU to ask every level is probed.
+fn hwcaps_subdirs(arch: ElfArch, machine: ElfMachine) -> &'static [&'static str] {
+    match (arch, machine) {
+        (ElfArch::Elf64, ElfMachine::X86_64) => &["x86-64-v4", "x86-64-v3", "x86-64-v2"],
+        (ElfArch::Elf64, ElfMachine::PowerPC) => &["power10", "power9", "power8"],
+        _ => &[],
     }
-    None
 }
 
-fn resolve_origin(bin_path: &Path, entry: &str) -> PathBuf {
-    if entry.starts_with("$ORIGIN") {
-        let rel = entry.trim_start_matches("$ORIGIN");
-        bin_path.parent().unwrap_or(Path::new("/")).join(rel)
-    } else {
-        PathBuf::from(entry)
+// Every search directory is preceded by its glibc-hwcaps/<level> subdirectories
+fn with_hwcaps(dirs: Vec<SearchDir>, arch: ElfArch, machine: ElfMachine) -> Vec<SearchDir> {
+    let levels = hwcaps_subdirs(arch, machine);
+    if levels.is_empty() {
+        return dirs;
     }
+    dirs.into_iter()
+        .flat_map(|dir| {
+            let mut expanded: Vec<SearchDir> = levels
+                .iter()
+                .map(|level| SearchDir {
+                    path: dir.path.join("glibc-hwcaps").join(level),
+                    source: dir.source,
+                })
+                .collect();
+            expanded.push(dir);
+            expanded
+        })
+        .collect()
 }
 
-fn open_and_map(path: &impl AsRef<Path>) -> io::Result<Mmap> {
-    let file = File::open(path)?;
-    let map = unsafe { Mmap::map(&file)? };
-    Ok(map)
+fn tagged(dirs: impl IntoIterator<Item = PathBuf>, source: SearchSource) -> Vec<SearchDir> {
+    dirs.into_iter()
+        .map(|path| SearchDir { path, source })
+        .collect()
 }
 
-fn empty_info() -> RlddRexInfo {
-    RlddRexInfo {
-        arch: ElfArch::Unknown,
-        elf_type: ElfType::Invalid,
-        deps: Vec::new(),
-    }
+#[cfg(feature = "enable_ld_library_path")]
+fn split_ld_library_path(value: &str, separator: char) -> Vec<SearchDir> {
+    value
+        .split(separator)
+        .map(|p| SearchDir {
+            path: PathBuf::from(if p.is_empty() { "." } else { p }),
+            source: SearchSource::LdLibraryPath,
+        })
+        .collect()
 }
 
-fn extra_lib_dirs_for_bin(path: &Path) -> Vec<PathBuf> {
-    let mut dirs = Vec::new();
-    let lib_names = ["lib", "lib64", "libs"];
-    let real_bin = match fs::canonicalize(path) {
-        Ok(p) => p,
-        Err(_) => path.to_path_buf(),
-    };
-
-    let bin_dir = match real_bin.parent() {
-        Some(p) => p.to_path_buf(),
-        None => return dirs,
-    };
+const EF_ARM_ABI_FLOAT_HARD: u32 = 0x400;
 
-    dirs.push(bin_dir.clone());
+const SHT_GNU_ATTRIBUTES: u32 = 0x6fff_fff5;
+const TAG_GNU_ABI_FP: u64 = 4;
 
-    for lib in lib_names {
-        dirs.push(bin_dir.join(lib));
-    }
+// The parts of the ABI that show up in loader file names besides the machine
+// and word size
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct AbiFlags {
+    e_flags: u32,
+    big_endian: bool,
+    // MIPS and PowerPC only record the float ABI in .gnu.attributes
+    soft_float: bool,
+}
 
-    if bin_dir.file_name().map_or(false, |f| f == "bin") {
-        if let Some(parent) = bin_dir.parent() {
-            for lib in lib_names {
-                dirs.push(parent.join(lib));
-            }
+impl AbiFlags {
+    fn of(elf: &Elf, data: &[u8]) -> Self {
+        let soft_float = match elf.header.e_machine {
+            EM_MIPS => gnu_abi_fp(elf, data) == Some(3),
+            EM_PPC => gnu_abi_fp(elf, data).is_some_and(|fp| fp & 3 == 2),
+            _ => false,
+        };
+        AbiFlags {
+            e_flags: elf.header.e_flags,
+            big_endian: !elf.little_endian,
+            soft_float,
         }
     }
-
-    dirs
 }
 
-fn inner(
-    path: &Path,
-    elf: &Elf,
-    visited: &mut HashSet<(u64, u64)>,
-    seen_libs: &mut HashSet<String>,
-    res: &mut Vec<(String, String)>,
-    dirs: &[PathBuf],
-    arch: ElfArch,
-    d: usize,
-) -> io::Result<()> {
-    if d > MAX_DEPTH {
-        eprintln!("Warning: max recursion depth at {:?}", path);
-        return Ok(());
-    }
-
-    if let Ok(meta) = fs::metadata(path) {
-        let key = (meta.dev(), meta.ino());
-        if !visited.insert(key) {
-            return Ok(());
+fn uleb128(data: &[u8], pos: &mut usize) -> Option<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *data.get(*pos)?;
+        *pos += 1;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
         }
-    } else {
-        eprintln!("Error access {:?}", path);
-        return Ok(());
     }
+    None
+}
 
-    let deps: Vec<_> = elf.libraries.iter().map(ToString::to_string).collect();
-    let paths: Vec<_> = elf
-        .rpaths
+// Tag_GNU_{MIPS,Power}_ABI_FP from the file-wide "gnu" attributes
+fn gnu_abi_fp(elf: &Elf, data: &[u8]) -> Option<u64> {
+    let sh = elf
+        .section_headers
         .iter()
-        .chain(&elf.runpaths)
-        .map(|s| resolve_origin(path, s))
-        .collect();
-
-    for dep in deps {
-        if !seen_libs.insert(dep.clone()) {
-            continue;
-        }
-
-        let display = find_library(&dep, dirs, &paths)
-            .map(|found| {
-                if let Ok(map) = open_and_map(&found) {
-                    if let Ok(s_elf) = Elf::parse(&map) {
-                        #[cfg(feature = "enable_ld_library_path")]
-                        if !is_same_arch(arch, &s_elf) {
-                            return "arch mismatch".into(); // Retorna aqui direto
+        .find(|sh| sh.sh_type == SHT_GNU_ATTRIBUTES)?;
+    let start = sh.sh_offset as usize;
+    let attrs = data.get(start..start.checked_add(sh.sh_size as usize)?)?;
+    let u32_at = |at: usize| -> Option<usize> {
+        let bytes: [u8; 4] = attrs.get(at..at + 4)?.try_into().ok()?;
+        let v = if elf.little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        };
+        Some(v as usize)
+    };
+    if attrs.first() != Some(&b'A') {
+        return None;
+    }
+    let mut pos = 1;
+    while pos < attrs.len() {
+        let len = u32_at(pos)?;
+        let end = pos.checked_add(len)?.min(attrs.len());
+        let vendor_end = pos + 4 + attrs.get(pos + 4..end)?.iter().position(|&b| b == 0)?;
+        if &attrs[pos + 4..vendor_end] == b"gnu" {
+            let mut sub = vendor_end + 1;
+            while sub < end {
+                let tag = *attrs.get(sub)?;
+                let sub_end = sub.checked_add(u32_at(sub + 1)?)?.min(end);
+                if tag == 1 {
+                    // Tag_File: tags below 32 take an integer, above that odd
+                    // ones a string, 32 itself both
+                    let mut at = sub + 5;
+                    while at < sub_end {
+                        let attr = uleb128(attrs, &mut at)?;
+                        if attr < 32 || attr % 2 == 0 {
+                            let value = uleb128(attrs, &mut at)?;
+                            if attr == TAG_GNU_ABI_FP {
+                                return Some(value);
+                            }
                         }
-
-                        if let Err(e) =
-                            inner(&found, &s_elf, visited, seen_libs, res, dirs, arch, d + 1)
-                        {
-                            eprintln!("Recursive error {:?}: {:?}", found, e);
+                        if attr == 32 || (attr > 32 && attr % 2 == 1) {
+                            at += attrs.get(at..sub_end)?.iter().position(|&b| b == 0)? + 1;
                         }
                     }
                 }
-                found.display().to_string()
-            })
-            .unwrap_or_else(|| "not found".into());
-
-        res.push((dep, display));
+                sub = sub_end.max(sub + 1);
+            }
+        }
+        pos = end.max(pos + 1);
     }
-
-    Ok(())
+    None
 }
 
-pub fn rldd_rex<P: AsRef<Path> + std::fmt::Debug>(path: P) -> io::Result<RlddRexInfo> {
-    let (mut libs, mut visited) = (HashSet::new(), HashSet::new());
-    let mut res = Vec::new();
-
-    let map = match open_and_map(&path) {
-        Ok(m) => m,
-        Err(e) => {
-            eprintln!("Fail to open or map {:?}: {}", path, e);
-            return Ok(empty_info());
-        }
+// musl names the file after its LDSO_ARCH, which folds in the word size
+// ("mips64"), byte order ("mipsel", "aarch64_be") and float ABI ("armhf",
+// "riscv64-sf"). The plain arch name is tried after.
+fn musl_path_files(arch: ElfArch, machine: ElfMachine, abi: AbiFlags) -> Vec<PathBuf> {
+    let base = match machine {
+        ElfMachine::X86 => "i386",
+        ElfMachine::Arm32 => "arm",
+        ElfMachine::Arm64 => "aarch64",
+        ElfMachine::Mips => "mips",
+        ElfMachine::PowerPC => "powerpc",
+        ElfMachine::RiscV32 => "riscv32",
+        ElfMachine::RiscV64 => "riscv64",
+        ElfMachine::S390x => "s390x",
+        ElfMachine::LoongArch64 => "loongarch64",
+        ElfMachine::X86_64 | ElfMachine::Unknown => "x86_64",
     };
-
-    let elf = match Elf::parse(&map) {
-        Ok(e) => e,
-        Err(e) => {
-            eprintln!("Fail to parser ELF {:?}: {}", path, e);
-            return Ok(empty_info());
+    let be = abi.big_endian;
+    let specific = match (machine, arch) {
+        (ElfMachine::Arm32, _) => {
+            let hf = abi.e_flags & EF_ARM_ABI_FLOAT_HARD != 0;
+            format!(
+                "arm{}{}",
+                if be { "eb" } else { "" },
+                if hf { "hf" } else { "" }
+            )
+        }
+        (ElfMachine::Arm64, _) if be => "aarch64_be".to_string(),
+        (ElfMachine::Mips, _) => format!(
+            "{}{}{}",
+            if arch == ElfArch::Elf64 {
+                "mips64"
+            } else {
+                "mips"
+            },
+            if be { "" } else { "el" },
+            if abi.soft_float { "-sf" } else { "" }
+        ),
+        (ElfMachine::PowerPC, ElfArch::Elf64) if !be => "powerpc64le".to_string(),
+        (ElfMachine::PowerPC, ElfArch::Elf64) => "powerpc64".to_string(),
+        (ElfMachine::PowerPC, _) if abi.soft_float => "powerpc-sf".to_string(),
+        (ElfMachine::RiscV32 | ElfMachine::RiscV64, _) => {
+            let float = match abi.e_flags & EF_RISCV_FLOAT_ABI {
+                0x0 => "-sf",
+                0x2 => "-sp",
+                _ => "",
+            };
+            format!("{}{}", base, float)
         }
+        (ElfMachine::X86_64, ElfArch::Elf32) => "x32".to_string(),
+        _ => base.to_string(),
     };
+    let mut files = vec![PathBuf::from(format!("/etc/ld-musl-{}.path", specific))];
+    if specific != base {
+        files.push(PathBuf::from(format!("/etc/ld-musl-{}.path", base)));
+    }
+    files
+}
 
-    let arch = [ElfArch::Elf32, ElfArch::Elf64][elf.is_64 as usize];
-    let machine = machine_from_e_machine(elf.header.e_machine);
-    let elf_type = get_elf_type(&elf);
+fn is_musl(interp: Option<&str>, options: &RlddOptions) -> bool {
+    match options.force_libc {
+        Some(family) => family == LibcFamily::Musl,
+        None => interp.is_some_and(|interp| interp.contains("musl")),
+    }
+}
 
-    if let Some(interp) = elf.interpreter {
-        if interp.contains("musl") {
-            let interp_path = PathBuf::from(interp);
+fn musl_conf_file(
+    arch: ElfArch,
+    machine: ElfMachine,
+    abi: AbiFlags,
+    sysroot: Option<&Path>,
+) -> Option<PathBuf> {
+    musl_path_files(arch, machine, abi)
+        .into_iter()
+        .map(|f| in_sysroot(sysroot, &f))
+        .find(|f| f.exists())
+}
 
-            let resolved_interp = if interp_path.exists() {
-                interp_path.canonicalize().unwrap_or(interp_path.clone())
-            } else {
-                interp_path.clone()
-            };
+fn build_search_dirs(
+    interp: Option<&str>,
+    abi: AbiFlags,
+    arch: ElfArch,
+    machine: ElfMachine,
+    options: &RlddOptions,
+) -> Vec<SearchDir> {
+    let sysroot = options.sysroot.as_deref();
+    let mut dirs = tagged(
+        [
+            PathBuf::from("/lib"),
+            PathBuf::from("/usr/lib"),
+            PathBuf::from("/usr/local/lib"),
+        ],
+        SearchSource::System,
+    );
+    // the loader never looks in libexec, keep it opt-in
+    if options.search_libexec {
+        dirs.extend(tagged(
+            [PathBuf::from("/usr/libexec"), PathBuf::from("/libexec")],
+            SearchSource::System,
+        ));
+    }
 
-            let lib_name = interp_path
-                .file_name()
-                .and_then(|name| name.to_str())
-                .unwrap_or(interp)
-                .to_string();
+    #[cfg(feature = "enable_ld_library_path")]
+    if let Ok(ld_path) = std::env::var("LD_LIBRARY_PATH") {
+        dirs.extend(split_ld_library_path(&ld_path, options.ld_path_separator));
+    }
 
-            res.push((lib_name.clone(), resolved_interp.display().to_string()));
-            libs.insert(lib_name);
+    if is_musl(interp, options) {
+        if let Some(musl_conf) = musl_conf_file(arch, machine, abi, sysroot)
+            && let Ok(content) = fs::read_to_string(musl_conf)
+        {
+            for line in content.lines() {
+                let trim = line.trim();
+                if !trim.is_empty() {
+                    dirs.push(SearchDir {
+                        path: in_sysroot(sysroot, Path::new(trim)),
+                        source: SearchSource::LdSoConf,
+                    });
+                }
+            }
         }
+    } else {
+        #[cfg(any(target_os = "linux", target_os = "solaris"))]
+        match read_ld_so_conf(options) {
+            Ok(ld_dirs) => dirs.extend(tagged(ld_dirs, SearchSource::LdSoConf)),
+            Err(e) => warning(options, format!("Error reading ld.so.conf: {}", e)),
+        }
+        dirs.extend(tagged(
+            riscv_abi_dirs(machine, abi.e_flags),
+            SearchSource::System,
+        ));
+        dirs.extend(tagged(
+            default_dirs_for_arch_and_machine(arch, machine),
+            SearchSource::System,
+        ));
+        dirs = with_hwcaps(dirs, arch, machine);
     }
 
-    let mut search_dirs = build_search_dirs(&elf, arch, machine);
-    search_dirs.extend(extra_lib_dirs_for_bin(path.as_ref()));
-
-    inner(
-        path.as_ref(),
-        &elf,
-        &mut visited,
-        &mut libs,
-        &mut res,
-        &search_dirs,
-        arch,
-        0,
-    )?;
+    for dir in dirs.iter_mut().filter(|d| d.source == SearchSource::System) {
+        dir.path = in_sysroot(sysroot, &dir.path);
+    }
+    dedup_search_dirs(dirs)
+}
+
+fn is_usrmerge(root: &Path) -> bool {
+    let lib = root.join("lib");
+    fs::symlink_metadata(&lib).is_ok_and(|m| m.file_type().is_symlink())
+        && fs::canonicalize(&lib).ok() == fs::canonicalize(root.join("usr/lib")).ok()
+}
+
+// On usrmerge systems /lib and /usr/lib are the same directory. Entries are
+// deduped by canonical path and each directory keeps the position and source
+// of its first spelling, so /lib searched first stays first even though it
+// is reported as /usr/lib.
+fn dedup_search_dirs(dirs: Vec<SearchDir>) -> Vec<SearchDir> {
+    let mut uniq = Vec::new();
+    let mut seen = HashSet::new();
+    for d in dirs {
+        let path = d.path.canonicalize().unwrap_or(d.path);
+        if seen.insert(path.clone()) {
+            uniq.push(SearchDir {
+                path,
+                source: d.source,
+            });
+        }
+    }
+    uniq
+}
+
+// ld.so order: DT_RPATH, LD_LIBRARY_PATH, DT_RUNPATH, then everything else
+fn search_order<'a>(
+    search_dirs: &'a [SearchDir],
+    paths: &'a [SearchDir],
+    priority: LdLibraryPathPriority,
+) -> impl Iterator<Item = &'a SearchDir> {
+    let from = |dirs: &'a [SearchDir], source: SearchSource| {
+        dirs.iter().filter(move |d| d.source == source)
+    };
+    let env = from(search_dirs, SearchSource::LdLibraryPath);
+    let runpath = from(paths, SearchSource::Runpath);
+    let (second, third) = match priority {
+        LdLibraryPathPriority::BeforeRunpath => (env.clone(), runpath.clone()),
+        LdLibraryPathPriority::AfterRpathOnly => (runpath.clone(), env.clone()),
+    };
+    // ld.so ignores DT_RPATH altogether once DT_RUNPATH is present
+    let has_runpath = runpath.clone().next().is_some();
+    from(paths, SearchSource::Rpath)
+        .filter(move |_| !has_runpath)
+        .chain(second)
+        .chain(third)
+        .chain(
+            search_dirs
+                .iter()
+                .filter(|d| d.source != SearchSource::LdLibraryPath),
+        )
+        .chain(
+            paths
+                .iter()
+                .filter(|d| !matches!(d.source, SearchSource::Rpath | SearchSource::Runpath)),
+        )
+}
+
+fn find_library<'a>(
+    lib: &str,
+    search_dirs: &'a [SearchDir],
+    paths: &'a [SearchDir],
+    priority: LdLibraryPathPriority,
+) -> Option<(PathBuf, &'a SearchDir)> {
+    for dir in search_order(search_dirs, paths, priority) {
+        let candidate = dir.path.join(lib);
+        if candidate.exists() {
+            return Some((candidate, dir));
+        }
+    }
+    None
+}
+
+// offline variant of find_library, existence is answered by the map only
+fn find_library_in<'a>(
+    lib: &str,
+    contents: &HashMap<PathBuf, Vec<String>>,
+    search_dirs: &'a [SearchDir],
+    paths: &'a [SearchDir],
+    priority: LdLibraryPathPriority,
+) -> Option<(PathBuf, &'a SearchDir)> {
+    search_order(search_dirs, paths, priority)
+        .find(|dir| {
+            contents
+                .get(&dir.path)
+                .is_some_and(|files| files.iter().any(|f| f == lib))
+        })
+        .map(|dir| (dir.path.join(lib), dir))
+}
 
-    Ok(RlddRexInfo {
-        arch,
-        elf_type,
-        deps: res,
+// vendor SDK convention: libfoo.so.1 -> libfoo-64.so.1
+fn arch_suffixed_name(lib: &str, arch: ElfArch) -> Option<String> {
+    let suffix = match arch {
+        ElfArch::Elf32 => "-32",
+        ElfArch::Elf64 => "-64",
+        ElfArch::Unknown => return None,
+    };
+    Some(match lib.find(".so") {
+        Some(pos) => format!("{}{}{}", &lib[..pos], suffix, &lib[pos..]),
+        None => format!("{}{}", lib, suffix),
     })
 }
 
+// What glibc's $LIB stands for: its per-arch slibdir. aarch64 keeps plain lib
+// even though it is 64-bit, x32 has its own libx32.
+fn lib_dir_name(arch: ElfArch, machine: ElfMachine) -> &'static str {
+    match (arch, machine) {
+        (ElfArch::Elf32, ElfMachine::X86_64) => "libx32",
+        (ElfArch::Elf32, _) | (_, ElfMachine::Arm64) => "lib",
+        _ => "lib64",
+    }
+}
+
+// AT_PLATFORM as the kernel reports it. Left unexpanded where it depends on
+// the exact CPU model rather than the architecture.
+fn platform_name(arch: ElfArch, machine: ElfMachine) -> Option<&'static str> {
+    match (arch, machine) {
+        (_, ElfMachine::X86_64) => Some("x86_64"),
+        (_, ElfMachine::X86) => Some("i686"),
+        (_, ElfMachine::Arm64) => Some("aarch64"),
+        (_, ElfMachine::Arm32) => Some("v7l"),
+        (ElfArch::Elf32, ElfMachine::Mips) => Some("mips"),
+        (ElfArch::Elf64, ElfMachine::Mips) => Some("mips64"),
+        _ => None,
+    }
+}
+
+// Replaces $NAME and ${NAME}, the bare form only when not followed by more
+// identifier characters
+fn expand_token(entry: &str, name: &str, value: &str) -> String {
+    let braced = format!("${{{}}}", name);
+    let bare = format!("${}", name);
+    let mut out = String::with_capacity(entry.len());
+    let mut rest = entry;
+    while let Some(pos) = rest.find('$') {
+        out.push_str(&rest[..pos]);
+        let tail = &rest[pos..];
+        if let Some(after) = tail.strip_prefix(braced.as_str()) {
+            out.push_str(value);
+            rest = after;
+        } else if let Some(after) = tail.strip_prefix(bare.as_str())
+            && !after.starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_')
+        {
+            out.push_str(value);
+            rest = after;
+        } else {
+            out.push('$');
+            rest = &tail[1..];
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+// Symlinks resolved the way they would be with the sysroot as /, where
+// canonicalize would follow absolute targets out of it
+fn follow_in_sysroot(sysroot: &Path, path: &Path) -> PathBuf {
+    let mut current = path.to_path_buf();
+    for _ in 0..40 {
+        let Ok(target) = fs::read_link(&current) else {
+            break;
+        };
+        current = if target.is_absolute() {
+            in_sysroot(Some(sysroot), &target)
+        } else {
+            current.parent().unwrap_or(Path::new("/")).join(target)
+        };
+    }
+    current
+}
+
+// Stands in for the file name of a binary analyzed from memory
+const IN_MEMORY: &str = "<memory>";
+
+// Bytes passed without a location have no $ORIGIN, entries using it are
+// dropped as the loader does for objects it cannot place
+fn has_origin(path: &Path) -> bool {
+    path != Path::new(IN_MEMORY)
+}
+
+// $ORIGIN is the directory of the object declaring the rpath, which is not
+// necessarily the root binary
+fn resolve_origin(obj_path: &Path, entry: &str, arch: ElfArch, machine: ElfMachine) -> PathBuf {
+    let mut entry = expand_token(entry, "LIB", lib_dir_name(arch, machine));
+    if let Some(platform) = platform_name(arch, machine) {
+        entry = expand_token(&entry, "PLATFORM", platform);
+    }
+    let rel = entry
+        .strip_prefix("${ORIGIN}")
+        .or_else(|| entry.strip_prefix("$ORIGIN"));
+    match rel {
+        Some(rel) => obj_path
+            .parent()
+            .unwrap_or(Path::new("/"))
+            .join(rel.trim_start_matches('/')),
+        None => PathBuf::from(&entry),
+    }
+}
+
+fn sniff_lib_kind(path: &Path) -> LibKind {
+    let mut head = [0u8; 512];
+    let len = match File::open(path).and_then(|mut f| io::Read::read(&mut f, &mut head)) {
+        Ok(n) => n,
+        Err(_) => return LibKind::Unknown,
+    };
+    let head = &head[..len];
+
+    if head.starts_with(b"!<arch>\n") {
+        return LibKind::Archive;
+    }
+
+    let text = String::from_utf8_lossy(head);
+    let text = text.trim_start();
+    if text.starts_with("/* GNU ld script")
+        || ["GROUP", "INPUT", "OUTPUT_FORMAT"]
+            .iter()
+            .any(|kw| text.starts_with(kw))
+    {
+        return LibKind::LinkerScript;
+    }
+
+    LibKind::Unknown
+}
+
+fn ld_script_inputs(path: &Path) -> Vec<String> {
+    let Ok(content) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+
+    let mut text = String::new();
+    let mut rest = content.as_str();
+    while let Some(start) = rest.find("/*") {
+        text.push_str(&rest[..start]);
+        rest = rest[start..]
+            .find("*/")
+            .map_or("", |end| &rest[start + end + 2..]);
+    }
+    text.push_str(rest);
+
+    let text = text
+        .replace('(', " ( ")
+        .replace(')', " ) ")
+        .replace(',', " ");
+    let mut tokens = text.split_whitespace();
+    let mut inputs = Vec::new();
+
+    while let Some(tok) = tokens.next() {
+        if !matches!(tok, "GROUP" | "INPUT") || tokens.next() != Some("(") {
+            continue;
+        }
+
+        let mut depth = 1;
+        for tok in tokens.by_ref() {
+            match tok {
+                "(" => depth += 1,
+                ")" => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                "AS_NEEDED" => {}
+                _ => inputs.push(tok.to_string()),
+            }
+        }
+    }
+    inputs
+}
+
+fn open_and_map(path: &impl AsRef<Path>) -> io::Result<Mmap> {
+    let file = File::open(path)?;
+    let map = unsafe { Mmap::map(&file)? };
+    Ok(map)
+}
+
+// Copy of the file with the section header table fields zeroed in the ELF
+// header, None when there is nothing to strip.
+fn without_section_headers(data: &[u8]) -> Option<Vec<u8>> {
+    let (shoff, ehsize) = match data.get(4)? {
+        1 => (0x20..0x24, 0x34),
+        2 => (0x28..0x30, 0x40),
+        _ => return None,
+    };
+    if data.len() < ehsize || data[shoff.clone()].iter().all(|&b| b == 0) {
+        return None;
+    }
+    let mut patched = data.to_vec();
+    patched[shoff.clone()].fill(0);
+    // e_shentsize, e_shnum, e_shstrndx
+    patched[ehsize - 6..ehsize].fill(0);
+    Some(patched)
+}
+
+// Stripped vendor binaries may carry a truncated or bogus section header
+// table. Everything needed for resolution is reachable from the program
+// headers, so retry without sections before giving up.
+fn parse_elf<'a>(data: &'a [u8], scratch: &'a mut Vec<u8>) -> goblin::error::Result<Elf<'a>> {
+    match Elf::parse(data) {
+        Ok(elf) => Ok(elf),
+        Err(e) => {
+            let Some(patched) = without_section_headers(data) else {
+                return Err(e);
+            };
+            *scratch = patched;
+            let scratch: &'a Vec<u8> = scratch;
+            Elf::parse(scratch).map_err(|_| e)
+        }
+    }
+}
+
+fn empty_info() -> RlddRexInfo {
+    RlddRexInfo {
+        arch: ElfArch::Unknown,
+        machine: ElfMachine::Unknown,
+        elf_type: ElfType::Invalid,
+        deps: Vec::new(),
+        libs: Vec::new(),
+        warnings: Vec::new(),
+        search_dirs: Vec::new(),
+        secure_exec: false,
+        ignored_dirs: Vec::new(),
+        symbol_providers: Vec::new(),
+        unresolved_symbols: Vec::new(),
+        debug_link: None,
+        debug_file: None,
+        undefined_symbols: Vec::new(),
+        executable_stack: false,
+        usrmerge: false,
+        relro: Relro::None,
+        relro_size: 0,
+        packed: None,
+        cpp_runtimes: Vec::new(),
+        cpp_runtime_conflict: false,
+        cpu_baseline: None,
+        libc_version: None,
+        version_requirements: BTreeMap::new(),
+        failed_at: None,
+        search_config_summary: SearchConfig::default(),
+        duplicate_needed: Vec::new(),
+        invalid_rpath_dirs: Vec::new(),
+        cycles: Vec::new(),
+        direct_deps: Vec::new(),
+        hash_style: HashStyle::None,
+        dynsym_count: 0,
+        conflicts: Vec::new(),
+    }
+}
+
+// UPX keeps its "UPX!" marker right after the program headers and in the
+// trailer. Other packers show up as a section-less image with a RWX segment
+// and no dynamic info of its own.
+fn detect_packer(elf: &Elf, data: &[u8]) -> Option<PackerKind> {
+    let head = &data[..data.len().min(1024)];
+    let tail = &data[data.len().saturating_sub(512)..];
+    let has_marker = |window: &[u8]| window.windows(4).any(|w| w == b"UPX!");
+    if has_marker(head) || has_marker(tail) {
+        return Some(PackerKind::Upx);
+    }
+
+    let rwx_load = elf
+        .program_headers
+        .iter()
+        .any(|ph| ph.p_type == PT_LOAD && ph.p_flags & (PF_W | PF_X) == PF_W | PF_X);
+    let has_dynamic = elf
+        .program_headers
+        .iter()
+        .any(|ph| matches!(ph.p_type, PT_DYNAMIC | PT_INTERP));
+    (elf.section_headers.is_empty() && rwx_load && !has_dynamic).then_some(PackerKind::Unknown)
+}
+
+// "x86-64-v3" -> 3, "power10" -> 10, "z15" -> 15
+fn hwcaps_level(name: &str) -> Option<u32> {
+    let level = name
+        .strip_prefix("x86-64-v")
+        .or_else(|| name.strip_prefix("power"))
+        .or_else(|| name.strip_prefix('z'))?;
+    level.parse().ok()
+}
+
+// The most advanced glibc-hwcaps subdirectory any loaded library came from,
+// the CPU has to support at least that level
+fn cpu_baseline(libs: &[ResolvedLib], deps: &[(String, DepStatus)]) -> Option<String> {
+    libs.iter()
+        .zip(deps)
+        .filter(|(_, (_, status))| status.is_resolved())
+        .filter_map(|(lib, _)| {
+            let mut parts = lib
+                .path
+                .as_ref()?
+                .iter()
+                .skip_while(|c| *c != "glibc-hwcaps");
+            parts.next()?;
+            let name = parts.next()?.to_str()?;
+            Some((hwcaps_level(name)?, name.to_string()))
+        })
+        .max_by_key(|(level, _)| *level)
+        .map(|(_, name)| name)
+}
+
+// deps and libs are parallel, sorted together. Unresolved entries go last
+// when sorting by path.
+fn sort_deps(
+    deps: Vec<(String, DepStatus)>,
+    libs: Vec<ResolvedLib>,
+    sort: DepSort,
+) -> (Vec<(String, DepStatus)>, Vec<ResolvedLib>) {
+    if sort == DepSort::None {
+        return (deps, libs);
+    }
+    let mut pairs: Vec<_> = deps.into_iter().zip(libs).collect();
+    match sort {
+        DepSort::None => {}
+        DepSort::BySoname => pairs.sort_by(|(a, _), (b, _)| a.0.cmp(&b.0)),
+        DepSort::ByPath => pairs.sort_by(|(a, a_lib), (b, b_lib)| {
+            let key = |name, lib: &ResolvedLib| (lib.path.is_none(), lib.path.clone(), name);
+            key(&a.0, a_lib).cmp(&key(&b.0, b_lib))
+        }),
+    }
+    pairs.into_iter().unzip()
+}
+
+// From the dynamic tags ld.so looks at, the section names don't matter
+fn hash_style(elf: &Elf) -> HashStyle {
+    let has = |tag| {
+        elf.dynamic
+            .as_ref()
+            .is_some_and(|d| d.dyns.iter().any(|d| d.d_tag == tag))
+    };
+    match (has(DT_GNU_HASH), has(DT_HASH)) {
+        (true, true) => HashStyle::Both,
+        (true, false) => HashStyle::Gnu,
+        (false, true) => HashStyle::Sysv,
+        (false, false) => HashStyle::None,
+    }
+}
+
+// DF_1_NODEFLIB: the built-in default directories are off limits
+fn is_nodeflib(elf: &Elf) -> bool {
+    elf.dynamic
+        .as_ref()
+        .is_some_and(|d| d.info.flags_1 & DF_1_NODEFLIB != 0)
+}
+
+// setuid/setgid binaries run with AT_SECURE set
+fn is_secure_exec(path: &Path) -> bool {
+    fs::metadata(path).is_ok_and(|m| m.mode() & 0o6000 != 0)
+}
+
+// Without PT_GNU_STACK the loader falls back to an executable stack
+fn has_exec_stack(elf: &Elf) -> bool {
+    match elf
+        .program_headers
+        .iter()
+        .find(|ph| ph.p_type == PT_GNU_STACK)
+    {
+        Some(ph) => ph.p_flags & PF_X != 0,
+        None => elf.dynamic.is_some() || elf.interpreter.is_some(),
+    }
+}
+
+// Same classification as checksec: full RELRO needs GNU_RELRO plus eager binding
+fn relro(elf: &Elf) -> (Relro, u64) {
+    let Some(ph) = elf
+        .program_headers
+        .iter()
+        .find(|ph| ph.p_type == PT_GNU_RELRO)
+    else {
+        return (Relro::None, 0);
+    };
+    let bind_now = elf.dynamic.as_ref().is_some_and(|dynamic| {
+        dynamic.dyns.iter().any(|d| d.d_tag == DT_BIND_NOW)
+            || dynamic.info.flags & DF_BIND_NOW != 0
+            || dynamic.info.flags_1 & DF_1_NOW != 0
+    });
+    let relro = if bind_now {
+        Relro::Full
+    } else {
+        Relro::Partial
+    };
+    (relro, ph.p_memsz)
+}
+
+fn is_external_symbol(sym: &goblin::elf::Sym) -> bool {
+    sym.st_name != 0 && matches!(sym.st_bind(), STB_GLOBAL | STB_WEAK)
+}
+
+fn undefined_symbols(elf: &Elf) -> Vec<String> {
+    elf.dynsyms
+        .iter()
+        .filter(|sym| is_external_symbol(sym) && sym.st_shndx == SHN_UNDEF as usize)
+        .filter_map(|sym| elf.dynstrtab.get_at(sym.st_name))
+        .map(String::from)
+        .collect()
+}
+
+// object files have no dynamic section, their imports live in .symtab
+fn object_undefined_symbols(elf: &Elf) -> Vec<String> {
+    elf.syms
+        .iter()
+        .filter(|sym| is_external_symbol(sym) && sym.st_shndx == SHN_UNDEF as usize)
+        .filter_map(|sym| elf.strtab.get_at(sym.st_name))
+        .map(String::from)
+        .collect()
+}
+
+fn exported_symbols(path: &Path) -> io::Result<HashSet<String>> {
+    let map = open_and_map(&path)?;
+    let mut scratch = Vec::new();
+    let elf =
+        parse_elf(&map, &mut scratch).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok(elf
+        .dynsyms
+        .iter()
+        .filter(|sym| is_external_symbol(sym) && sym.st_shndx != SHN_UNDEF as usize)
+        .filter_map(|sym| elf.dynstrtab.get_at(sym.st_name))
+        .map(String::from)
+        .collect())
+}
+
+// glibc defines a GLIBC_x.y version node for every release that added
+// symbols, the newest one is the library's own version. musl carries nothing
+// comparable in its dynamic tables.
+fn glibc_version(path: &Path) -> Option<String> {
+    let map = open_and_map(&path).ok()?;
+    let mut scratch = Vec::new();
+    let elf = parse_elf(&map, &mut scratch).ok()?;
+    let verdef = elf.verdef.as_ref()?;
+    verdef
+        .iter()
+        .flat_map(|def| def.iter().collect::<Vec<_>>())
+        .filter_map(|aux| elf.dynstrtab.get_at(aux.vda_name)?.strip_prefix("GLIBC_"))
+        .filter_map(|v| {
+            let parts: Option<Vec<u32>> = v.split('.').map(|n| n.parse().ok()).collect();
+            Some((parts?, v.to_string()))
+        })
+        .max_by(|a, b| a.0.cmp(&b.0))
+        .map(|(_, v)| v)
+}
+
+fn version_requirements(elf: &Elf) -> BTreeMap<String, Vec<String>> {
+    let mut needs: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let Some(verneed) = &elf.verneed else {
+        return needs;
+    };
+    for need in verneed.iter() {
+        let Some(lib) = elf.dynstrtab.get_at(need.vn_file) else {
+            continue;
+        };
+        let versions = needs.entry(lib.to_string()).or_default();
+        for aux in need.iter() {
+            if let Some(version) = elf.dynstrtab.get_at(aux.vna_name)
+                && !versions.iter().any(|v| v == version)
+            {
+                versions.push(version.to_string());
+            }
+        }
+    }
+    needs
+}
+
+// Exports of every loadable library in load order (breadth first), so the
+// first match is the one the dynamic linker would bind to
+fn closure_exports<'a>(
+    libs: &'a [ResolvedLib],
+    res: &[(String, DepStatus)],
+    options: &RlddOptions,
+) -> Vec<(&'a str, HashSet<String>)> {
+    let mut order: Vec<_> = libs
+        .iter()
+        .zip(res)
+        .filter(|(lib, (_, status))| {
+            status.is_resolved() && lib.kind.is_some_and(|k| k.is_shared())
+        })
+        .filter_map(|(lib, _)| Some((lib, lib.path.as_ref()?)))
+        .collect();
+    order.sort_by_key(|(lib, _)| lib.depth);
+
+    let mut exports = Vec::new();
+    for (lib, path) in order {
+        match exported_symbols(path) {
+            Ok(syms) => exports.push((lib.name.as_str(), syms)),
+            Err(e) => warning(
+                options,
+                format!("Fail to read symbols of {:?}: {}", path, e),
+            ),
+        }
+    }
+    exports
+}
+
+fn symbol_providers(elf: &Elf, exports: &[(&str, HashSet<String>)]) -> Vec<(String, String)> {
+    undefined_symbols(elf)
+        .into_iter()
+        .filter_map(|sym| {
+            let (lib, _) = exports.iter().find(|(_, syms)| syms.contains(&sym))?;
+            Some((sym, lib.to_string()))
+        })
+        .collect()
+}
+
+// What ld.so would fail to bind at load time. Weak references may stay
+// unresolved, those are left out.
+fn unresolved_symbols(elf: &Elf, exports: &[(&str, HashSet<String>)]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    elf.dynsyms
+        .iter()
+        .filter(|sym| {
+            sym.st_name != 0 && sym.st_bind() == STB_GLOBAL && sym.st_shndx == SHN_UNDEF as usize
+        })
+        .filter_map(|sym| elf.dynstrtab.get_at(sym.st_name))
+        .filter(|sym| !exports.iter().any(|(_, syms)| syms.contains(*sym)))
+        .filter(|sym| seen.insert(*sym))
+        .map(String::from)
+        .collect()
+}
+
+fn debug_link(elf: &Elf, data: &[u8]) -> Option<String> {
+    let sh = elf
+        .section_headers
+        .iter()
+        .find(|sh| elf.shdr_strtab.get_at(sh.sh_name) == Some(".gnu_debuglink"))?;
+    let start = sh.sh_offset as usize;
+    let raw = data.get(start..start.checked_add(sh.sh_size as usize)?)?;
+    let name = raw.split(|&b| b == 0).next()?;
+    (!name.is_empty()).then(|| String::from_utf8_lossy(name).into_owned())
+}
+
+// Same lookup order as gdb: next to the binary, its .debug subdir, then
+// each global debug dir with and without the binary's directory appended.
+fn find_debug_file(bin_path: &Path, link: &str, debug_dirs: &[PathBuf]) -> Option<PathBuf> {
+    let real_bin = fs::canonicalize(bin_path).unwrap_or(bin_path.to_path_buf());
+    let bin_dir = real_bin.parent()?;
+    let mut candidates = vec![bin_dir.join(link), bin_dir.join(".debug").join(link)];
+    for dir in debug_dirs {
+        candidates.push(
+            dir.join(bin_dir.strip_prefix("/").unwrap_or(bin_dir))
+                .join(link),
+        );
+        candidates.push(dir.join(link));
+    }
+    candidates
+        .into_iter()
+        .find(|c| c.is_file() && c.as_path() != real_bin)
+}
+
+fn extra_lib_dirs_for_bin(path: &Path) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    let lib_names = ["lib", "lib64", "libs"];
+    let real_bin = match fs::canonicalize(path) {
+        Ok(p) => p,
+        Err(_) => path.to_path_buf(),
+    };
+
+    let bin_dir = match real_bin.parent() {
+        Some(p) => p.to_path_buf(),
+        None => return dirs,
+    };
+
+    dirs.push(bin_dir.clone());
+
+    for lib in lib_names {
+        dirs.push(bin_dir.join(lib));
+    }
+
+    if bin_dir.file_name().is_some_and(|f| f == "bin")
+        && let Some(parent) = bin_dir.parent()
+    {
+        for lib in lib_names {
+            dirs.push(parent.join(lib));
+        }
+    }
+
+    dirs
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct ElfObject {
+    is_64: bool,
+    kind: LibKind,
+    libraries: Vec<String>,
+    rpaths: Vec<String>,
+    runpaths: Vec<String>,
+    exec_stack: bool,
+    static_tls: bool,
+    e_machine: u16,
+    hash_style: HashStyle,
+    dynsym_count: usize,
+    soname: Option<String>,
+}
+
+impl ElfObject {
+    fn from_elf(elf: &Elf) -> Self {
+        ElfObject {
+            is_64: elf.is_64,
+            kind: [LibKind::Static, LibKind::Shared][elf.dynamic.is_some() as usize],
+            libraries: elf.libraries.iter().map(ToString::to_string).collect(),
+            rpaths: elf.rpaths.iter().map(ToString::to_string).collect(),
+            runpaths: elf.runpaths.iter().map(ToString::to_string).collect(),
+            exec_stack: has_exec_stack(elf),
+            static_tls: elf
+                .dynamic
+                .as_ref()
+                .is_some_and(|d| d.info.flags & DF_STATIC_TLS != 0),
+            e_machine: elf.header.e_machine,
+            hash_style: hash_style(elf),
+            dynsym_count: elf.dynsyms.len(),
+            soname: elf.soname.map(String::from),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct FileStamp {
+    mtime: i64,
+    mtime_nsec: i64,
+    size: u64,
+}
+
+impl FileStamp {
+    fn of(meta: &fs::Metadata) -> Self {
+        FileStamp {
+            mtime: meta.mtime(),
+            mtime_nsec: meta.mtime_nsec(),
+            size: meta.size(),
+        }
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct CachedObject {
+    #[cfg_attr(not(feature = "serde"), allow(dead_code))]
+    path: PathBuf,
+    stamp: FileStamp,
+    obj: Arc<ElfObject>,
+}
+
+#[derive(Default)]
+pub struct Resolver {
+    options: RlddOptions,
+    objects: Mutex<HashMap<(u64, u64), CachedObject>>,
+    parsed: AtomicUsize,
+    probes: AtomicUsize,
+    shared_seen: Mutex<HashMap<(ElfArch, ElfMachine), Seen>>,
+    listings: Mutex<HashMap<PathBuf, DirListing>>,
+}
+
+// Directory contents keyed by the directory's mtime: dropping a new library
+// into a dir bumps it, so cached negative lookups don't outlive the change.
+struct DirListing {
+    mtime: (i64, i64),
+    first_bytes: [u64; 4],
+    names: HashSet<std::ffi::OsString>,
+}
+
+impl DirListing {
+    fn new(mtime: (i64, i64), names: HashSet<std::ffi::OsString>) -> Self {
+        let mut first_bytes = [0u64; 4];
+        for b in names.iter().filter_map(|n| n.as_encoded_bytes().first()) {
+            first_bytes[(b >> 6) as usize] |= 1 << (b & 63);
+        }
+        DirListing {
+            mtime,
+            first_bytes,
+            names,
+        }
+    }
+
+    // a bit test rules out most misses in huge dirs before hashing the name
+    fn contains(&self, lib: &str) -> bool {
+        let Some(&b) = lib.as_bytes().first() else {
+            return false;
+        };
+        self.first_bytes[(b >> 6) as usize] & (1 << (b & 63)) != 0
+            && self.names.contains(std::ffi::OsStr::new(lib))
+    }
+}
+
+// visited inodes and reported libs carried across roots with DedupScope::Shared,
+// kept per (arch, machine) so a 32-bit root never dedups against 64-bit libs
+#[derive(Default)]
+struct Seen {
+    visited: HashSet<(u64, u64)>,
+    libs: HashSet<String>,
+}
+
+impl Resolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_options(options: RlddOptions) -> Self {
+        Resolver {
+            options,
+            ..Self::default()
+        }
+    }
+
+    pub fn options(&self) -> &RlddOptions {
+        &self.options
+    }
+
+    fn object(&self, path: &Path) -> io::Result<Arc<ElfObject>> {
+        let meta = fs::metadata(path)?;
+        let key = (meta.dev(), meta.ino());
+        let stamp = FileStamp::of(&meta);
+
+        if let Some(cached) = self.objects.lock().unwrap().get(&key)
+            && cached.stamp == stamp
+        {
+            return Ok(cached.obj.clone());
+        }
+
+        let map = open_and_map(&path)?;
+        let mut scratch = Vec::new();
+        let elf = parse_elf(&map, &mut scratch)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        check_header(&elf)?;
+        let obj = Arc::new(ElfObject::from_elf(&elf));
+        self.parsed.fetch_add(1, Ordering::Relaxed);
+
+        let cached = CachedObject {
+            path: path.to_path_buf(),
+            stamp,
+            obj: obj.clone(),
+        };
+        self.objects.lock().unwrap().insert(key, cached);
+        Ok(obj)
+    }
+
+    fn dir_has(&self, dir: &Path, lib: &str) -> bool {
+        let Ok(meta) = fs::metadata(dir) else {
+            return false;
+        };
+        let mtime = (meta.mtime(), meta.mtime_nsec());
+        let mut listings = self.listings.lock().unwrap();
+        let stale = listings.get(dir).is_none_or(|l| l.mtime != mtime);
+        if stale {
+            let names = match fs::read_dir(dir) {
+                Ok(entries) => entries.flatten().map(|e| e.file_name()).collect(),
+                Err(_) => HashSet::new(),
+            };
+            listings.insert(dir.to_path_buf(), DirListing::new(mtime, names));
+        }
+        listings[dir].contains(lib)
+    }
+
+    fn find_library_cached<'a>(
+        &self,
+        lib: &str,
+        search_dirs: &'a [SearchDir],
+        paths: &'a [SearchDir],
+    ) -> Option<(PathBuf, &'a SearchDir)> {
+        search_order(search_dirs, paths, self.options.ld_library_path_priority)
+            .filter(|dir| self.dir_has(&dir.path, lib))
+            .map(|dir| (dir.path.join(lib), dir))
+            .find(|(candidate, _)| {
+                self.probes.fetch_add(1, Ordering::Relaxed);
+                candidate.exists()
+            })
+    }
+
+    fn find_in(
+        &self,
+        lib: &str,
+        search_dirs: &[SearchDir],
+        paths: &[SearchDir],
+    ) -> Option<(PathBuf, SearchDir)> {
+        if let Some(path) = self.options.virtual_libs.get(lib) {
+            let dir = SearchDir {
+                path: path.parent().unwrap_or(Path::new("/")).to_path_buf(),
+                source: SearchSource::Virtual,
+            };
+            return Some((path.clone(), dir));
+        }
+        let priority = self.options.ld_library_path_priority;
+        let found = match &self.options.dir_contents {
+            Some(contents) => find_library_in(lib, contents, search_dirs, paths, priority),
+            None if self.options.cache_dir_listings => {
+                self.find_library_cached(lib, search_dirs, paths)
+            }
+            None => find_library(lib, search_dirs, paths, priority),
+        };
+        found.map(|(p, dir)| (p, dir.clone()))
+    }
+
+    pub fn resolve_many(
+        &self,
+        names: &[String],
+        arch: ElfArch,
+        machine: ElfMachine,
+    ) -> Vec<(String, DepStatus)> {
+        let dirs = build_search_dirs(None, AbiFlags::default(), arch, machine, &self.options);
+        let dirs = self.with_extra_dirs(dirs, Vec::new());
+        names
+            .iter()
+            .map(|name| {
+                let status = match self.find_in(name, &dirs, &[]) {
+                    Some((path, _)) => DepStatus::Found(path),
+                    None if self.options.assume_present.contains(name) => DepStatus::Assumed,
+                    None => DepStatus::NotFound,
+                };
+                (name.clone(), status)
+            })
+            .collect()
+    }
+
+    fn warn(&self, message: String) {
+        warning(&self.options, message)
+    }
+
+    fn root_path(&self, path: &Path) -> PathBuf {
+        let path = &match &self.options.base_dir {
+            Some(base) if path.is_relative() => base.join(path),
+            _ => path.to_path_buf(),
+        };
+        if self.options.canonicalize_root {
+            fs::canonicalize(path).unwrap_or(path.to_path_buf())
+        } else {
+            path.to_path_buf()
+        }
+    }
+
+    // What topo, tree and weight build their views from
+    fn closure_graph(&self, path: &Path) -> Result<ClosureGraph, RlddError> {
+        let root = self.root_path(path);
+        let info = self.rldd_rex(&root)?;
+        let mut libs = HashMap::new();
+        for (lib, (_, status)) in info.libs.into_iter().zip(info.deps) {
+            libs.entry(lib.name.clone()).or_insert((lib, status));
+        }
+        Ok(ClosureGraph {
+            root,
+            needed: info.direct_deps,
+            libs,
+        })
+    }
+
+    pub fn rldd_rex<P: AsRef<Path> + std::fmt::Debug>(
+        &self,
+        path: P,
+    ) -> Result<RlddRexInfo, RlddError> {
+        let path = self.root_path(path.as_ref());
+        let map = open_and_map(&path)?;
+        if !map.starts_with(b"\x7fELF") {
+            return Err(RlddError::NotElf);
+        }
+
+        let mut scratch = Vec::new();
+        let elf =
+            parse_elf(&map, &mut scratch).map_err(|e| RlddError::ParseFailed(e.to_string()))?;
+        check_header(&elf)?;
+        Ok(self.info_for(&path, &elf, &map)?)
+    }
+
+    // `origin` is the directory $ORIGIN expands to for the root, the
+    // libraries themselves are found on disk as usual
+    pub fn rldd_rex_from_bytes(
+        &self,
+        data: &[u8],
+        origin: Option<&Path>,
+    ) -> Result<RlddRexInfo, RlddError> {
+        if !data.starts_with(b"\x7fELF") {
+            return Err(RlddError::NotElf);
+        }
+        let mut scratch = Vec::new();
+        let elf =
+            parse_elf(data, &mut scratch).map_err(|e| RlddError::ParseFailed(e.to_string()))?;
+        check_header(&elf)?;
+        let path = match origin {
+            Some(dir) => dir.join(IN_MEMORY),
+            None => PathBuf::from(IN_MEMORY),
+        };
+        Ok(self.info_for(&path, &elf, data)?)
+    }
+
+    // Old behavior: a file that cannot be opened or parsed is reported on
+    // stderr and comes back as an empty result.
+    pub fn rldd_rex_lenient<P: AsRef<Path> + std::fmt::Debug>(
+        &self,
+        path: P,
+    ) -> io::Result<RlddRexInfo> {
+        match self.rldd_rex(&path) {
+            Err(RlddError::Io(e)) => {
+                self.warn(format!("Fail to open or map {:?}: {}", path, e));
+                Ok(empty_info())
+            }
+            Err(e @ (RlddError::NotElf | RlddError::ParseFailed(_))) => {
+                self.warn(format!("Fail to parser ELF {:?}: {}", path, e));
+                Ok(empty_info())
+            }
+            res => res.map_err(io::Error::from),
+        }
+    }
+
+    // everything rldd_rex reports once the root is parsed
+    fn info_for(&self, path: &Path, elf: &Elf, map: &[u8]) -> io::Result<RlddRexInfo> {
+        let mut walk = self.walk_root(path, elf, map, None)?;
+        let packed = detect_packer(elf, map);
+        if let Some(packer) = packed {
+            walk.warn(format!(
+                "Warning: {:?} looks packed ({:?}), dependencies are likely incomplete",
+                path, packer
+            ));
+        }
+        let (resolve, validate) = (self.options.resolve_symbols, self.options.validate_symbols);
+        let exports = if resolve || validate {
+            closure_exports(&walk.libs, &walk.res, &self.options)
+        } else {
+            Vec::new()
+        };
+        let symbol_providers = if resolve {
+            symbol_providers(elf, &exports)
+        } else {
+            Vec::new()
+        };
+        let unresolved_symbols = if validate {
+            unresolved_symbols(elf, &exports)
+        } else {
+            Vec::new()
+        };
+        let debug_link = debug_link(elf, map);
+        let debug_file = match &debug_link {
+            Some(link) if !self.options.debug_search_dirs.is_empty() => {
+                find_debug_file(path, link, &self.options.debug_search_dirs)
+            }
+            _ => None,
+        };
+
+        let elf_type = get_elf_type(elf);
+        let undefined_symbols = if elf_type.is_relocatable() {
+            object_undefined_symbols(elf)
+        } else {
+            Vec::new()
+        };
+
+        let (relro, relro_size) = relro(elf);
+        let cpp_runtimes = conflict::cpp_runtimes(&walk.res);
+        let cpp_runtime_conflict = conflict::has_cpp_runtime_conflict(&cpp_runtimes);
+        let cpu_baseline = cpu_baseline(&walk.libs, &walk.res);
+        let libc_version = walk
+            .libs
+            .iter()
+            .zip(&walk.res)
+            .find(|(lib, (_, status))| lib.name == "libc.so.6" && status.is_resolved())
+            .and_then(|(lib, _)| glibc_version(lib.path.as_ref()?));
+        let conflicts = conflict::find_conflicts(
+            &walk.libs,
+            &walk.res,
+            &walk.alternates,
+            &walk.load_rank,
+            &walk.loaded,
+        );
+        let (deps, libs) = sort_deps(walk.res, walk.libs, self.options.sort);
+
+        Ok(RlddRexInfo {
+            arch: walk.arch,
+            machine: walk.machine,
+            elf_type,
+            deps,
+            libs,
+            warnings: walk.warnings,
+            search_dirs: walk.dirs,
+            secure_exec: walk.secure,
+            ignored_dirs: walk.ignored_dirs,
+            symbol_providers,
+            unresolved_symbols,
+            debug_link,
+            debug_file,
+            undefined_symbols,
+            executable_stack: has_exec_stack(elf),
+            hash_style: hash_style(elf),
+            dynsym_count: elf.dynsyms.len(),
+            usrmerge: is_usrmerge(self.options.sysroot.as_deref().unwrap_or(Path::new("/"))),
+            conflicts,
+            relro,
+            relro_size,
+            packed,
+            cpp_runtimes,
+            cpp_runtime_conflict,
+            cpu_baseline,
+            libc_version,
+            version_requirements: version_requirements(elf),
+            failed_at: walk.failed_at,
+            search_config_summary: walk.config,
+            duplicate_needed: walk.duplicate_needed,
+            invalid_rpath_dirs: walk.invalid_rpath_dirs,
+            cycles: walk.cycles,
+            direct_deps: elf.libraries.iter().map(|s| s.to_string()).collect(),
+        })
+    }
+
+    pub fn depends_on<P: AsRef<Path> + std::fmt::Debug>(&self, path: P, soname: &str) -> bool {
+        let path = self.root_path(path.as_ref());
+        let Ok(map) = open_and_map(&path) else {
+            self.warn(format!("Fail to open or map {:?}", path));
+            return false;
+        };
+        let mut scratch = Vec::new();
+        let Ok(elf) = parse_elf(&map, &mut scratch) else {
+            self.warn(format!("Fail to parser ELF {:?}", path));
+            return false;
+        };
+
+        if let Err(e) = check_header(&elf) {
+            self.warn(format!("Fail to parser ELF {:?}: {}", path, e));
+            return false;
+        }
+        match self.walk_root(&path, &elf, &map, Some(soname)) {
+            Ok(walk) => walk.stop && walk.failed_at.is_none(),
+            Err(_) => false,
+        }
+    }
+
+    fn closure_inodes(&self, path: &Path) -> HashSet<(u64, u64)> {
+        let Ok(info) = self.rldd_rex(path) else {
+            return HashSet::new();
+        };
+        info.libs
+            .iter()
+            .zip(&info.deps)
+            .filter(|(_, (_, status))| status.is_resolved())
+            .filter_map(|(lib, _)| fs::metadata(lib.path.as_ref()?).ok())
+            .map(|meta| (meta.dev(), meta.ino()))
+            .collect()
+    }
+
+    pub fn missing_from_base<P: AsRef<Path> + std::fmt::Debug>(
+        &self,
+        path: P,
+        base_files: &HashSet<PathBuf>,
+    ) -> Vec<ResolvedLib> {
+        let Ok(info) = self.rldd_rex(path) else {
+            return Vec::new();
+        };
+        info.libs
+            .into_iter()
+            .zip(&info.deps)
+            .filter(|(_, (_, status))| status.is_resolved())
+            .filter_map(|(lib, _)| {
+                let path = lib.path.as_ref()?;
+                let real = fs::canonicalize(path).unwrap_or(path.clone());
+                (!base_files.contains(path) && !base_files.contains(&real)).then_some(lib)
+            })
+            .collect()
+    }
+
+    pub fn same_closure<A: AsRef<Path>, B: AsRef<Path>>(&self, a: A, b: B) -> bool {
+        self.closure_inodes(a.as_ref()) == self.closure_inodes(b.as_ref())
+    }
+
+    // extra_dirs spliced in where the options ask, then the bin dir guesses,
+    // without repeats
+    fn with_extra_dirs(
+        &self,
+        mut dirs: Vec<SearchDir>,
+        bin_dirs: Vec<SearchDir>,
+    ) -> Vec<SearchDir> {
+        let extra = tagged(self.options.extra_dirs.iter().cloned(), SearchSource::Extra);
+        match self.options.extra_dirs_position {
+            ExtraDirsPosition::Prepend => {
+                dirs.splice(0..0, extra);
+            }
+            ExtraDirsPosition::Append => dirs.extend(extra),
+        }
+        dirs.extend(bin_dirs);
+        dedup_search_dirs(dirs)
+    }
+
+    // Search dirs shared by the whole walk of `path` and the ones the secure
+    // exec rules dropped
+    fn root_dirs(&self, path: &Path, elf: &Elf, data: &[u8]) -> (Vec<SearchDir>, Vec<SearchDir>) {
+        let arch = [ElfArch::Elf32, ElfArch::Elf64][elf.is_64 as usize];
+        let machine = machine_from_e_machine(elf.header.e_machine, arch);
+
+        let mut dirs = build_search_dirs(
+            elf.interpreter,
+            AbiFlags::of(elf, data),
+            arch,
+            machine,
+            &self.options,
+        );
+        if is_nodeflib(elf) {
+            dirs.retain(|d| d.source != SearchSource::System);
+        }
+        let bin_dirs = if has_origin(path) {
+            tagged(extra_lib_dirs_for_bin(path), SearchSource::BinDir)
+        } else {
+            Vec::new()
+        };
+        let dirs = self.with_extra_dirs(dirs, bin_dirs);
+
+        if is_secure_exec(path) {
+            dirs.into_iter()
+                .partition(|d| matches!(d.source, SearchSource::System | SearchSource::LdSoConf))
+        } else {
+            (dirs, Vec::new())
+        }
+    }
+
+    // The directory list in the order it is searched, for a binary without
+    // RPATH/RUNPATH. Each object's own entries are searched around these.
+    pub fn search_dirs_for<P: AsRef<Path>>(&self, path: P) -> io::Result<Vec<PathBuf>> {
+        let path = self.root_path(path.as_ref());
+        let map = open_and_map(&path)?;
+        let mut scratch = Vec::new();
+        let elf = parse_elf(&map, &mut scratch)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        check_header(&elf)?;
+        let (dirs, _) = self.root_dirs(&path, &elf, &map);
+        let priority = self.options.ld_library_path_priority;
+        Ok(search_order(&dirs, &[], priority)
+            .map(|d| d.path.clone())
+            .collect())
+    }
+
+    fn walk_root(
+        &self,
+        path: &Path,
+        elf: &Elf,
+        data: &[u8],
+        target: Option<&str>,
+    ) -> io::Result<Walk<'_>> {
+        let arch = [ElfArch::Elf32, ElfArch::Elf64][elf.is_64 as usize];
+        let machine = machine_from_e_machine(elf.header.e_machine, arch);
+        let (dirs, ignored_dirs) = self.root_dirs(path, elf, data);
+        let secure = is_secure_exec(path);
+        let nodeflib = is_nodeflib(elf);
+
+        let options = &self.options;
+        let musl = is_musl(elf.interpreter, options);
+        let config = SearchConfig {
+            #[cfg(feature = "enable_ld_library_path")]
+            ld_library_path: std::env::var("LD_LIBRARY_PATH").ok(),
+            #[cfg(not(feature = "enable_ld_library_path"))]
+            ld_library_path: None,
+            ld_so_conf: !musl && cfg!(any(target_os = "linux", target_os = "solaris")),
+            extra_ld_conf_files: options.extra_ld_conf_files.clone(),
+            musl_path_file: musl
+                .then(|| {
+                    let sysroot = options.sysroot.as_deref();
+                    musl_conf_file(arch, machine, AbiFlags::of(elf, data), sysroot)
+                })
+                .flatten(),
+            default_dirs: !nodeflib,
+            bin_dir_heuristic: !secure,
+            dir_contents: options.dir_contents.is_some(),
+            cache_dir_listings: options.cache_dir_listings,
+            sysroot: options.sysroot.clone(),
+            base_dir: options.base_dir.clone(),
+            extra_dirs: options.extra_dirs.clone(),
+        };
+
+        let mut walk = Walk {
+            resolver: self,
+            visited: HashSet::new(),
+            seen_libs: HashSet::new(),
+            res: Vec::new(),
+            libs: Vec::new(),
+            alternates: Vec::new(),
+            load_rank: Vec::new(),
+            loaded: HashMap::new(),
+            warnings: Vec::new(),
+            dirs,
+            secure,
+            ignored_dirs,
+            arch,
+            machine,
+            target: target.map(String::from),
+            stop: false,
+            failed_at: None,
+            config,
+            duplicate_needed: Vec::new(),
+            invalid_rpath_dirs: Vec::new(),
+            chain: Vec::new(),
+            cycles: Vec::new(),
+            depth_cut: false,
+        };
+
+        let shared = target.is_none() && self.options.dedup_scope == DedupScope::Shared;
+        if shared && let Some(seen) = self.shared_seen.lock().unwrap().get(&(arch, machine)) {
+            walk.visited = seen.visited.clone();
+            walk.seen_libs = seen.libs.clone();
+        }
+
+        // musl's loader is reported first, glibc's after the walk
+        if musl && let Some(interp) = elf.interpreter {
+            walk.push_interp(interp);
+        }
+
+        if !walk.stop {
+            let root = ElfObject::from_elf(elf);
+            walk.inner(path, &root, 0)?;
+            // ldd lists the glibc loader after everything else, unless some
+            // library already pulled it in through DT_NEEDED
+            if !musl
+                && !walk.stop
+                && let Some(interp) = elf.interpreter
+            {
+                walk.push_interp(interp);
+            }
+            if target.is_none() {
+                if self.options.order == DepOrder::LoadOrder {
+                    walk.load_order(path, &root);
+                }
+                walk.rank_loads(path, &root);
+            }
+        }
+
+        if shared {
+            let mut shared_seen = self.shared_seen.lock().unwrap();
+            let seen = shared_seen.entry((walk.arch, walk.machine)).or_default();
+            seen.visited.extend(walk.visited.iter().copied());
+            seen.libs.extend(walk.seen_libs.iter().cloned());
+        }
+        Ok(walk)
+    }
+}
+
+type Lookup = Option<(PathBuf, SearchDir)>;
+
+// The root, its own DT_NEEDED entries and the first library rldd_rex
+// reported under each name
+struct ClosureGraph {
+    root: PathBuf,
+    needed: Vec<String>,
+    libs: HashMap<String, (ResolvedLib, DepStatus)>,
+}
+
+impl ClosureGraph {
+    // only libraries that resolved to a file
+    fn resolved(&self, name: &str) -> Option<&ResolvedLib> {
+        self.libs
+            .get(name)
+            .filter(|(lib, status)| status.is_resolved() && lib.path.is_some())
+            .map(|(lib, _)| lib)
+    }
+}
+
+struct Walk<'a> {
+    resolver: &'a Resolver,
+    visited: HashSet<(u64, u64)>,
+    seen_libs: HashSet<String>,
+    res: Vec<(String, DepStatus)>,
+    libs: Vec<ResolvedLib>,
+    // (name, path) a repeated dependency would resolve to from another object
+    alternates: Vec<(String, PathBuf)>,
+    // position of each entry of libs in loader order
+    load_rank: Vec<usize>,
+    // soname -> file actually loaded, when it differs by requester
+    loaded: HashMap<String, PathBuf>,
+    warnings: Vec<String>,
+    dirs: Vec<SearchDir>,
+    secure: bool,
+    ignored_dirs: Vec<SearchDir>,
+    arch: ElfArch,
+    machine: ElfMachine,
+    target: Option<String>,
+    stop: bool,
+    // first unresolved dependency when fail_fast cut the walk short
+    failed_at: Option<String>,
+    config: SearchConfig,
+    duplicate_needed: Vec<String>,
+    // RPATH/RUNPATH entries, after $ORIGIN expansion, that are not directories
+    invalid_rpath_dirs: Vec<PathBuf>,
+    // objects on the DT_NEEDED chain being walked, root first
+    chain: Vec<(String, Option<(u64, u64)>)>,
+    cycles: Vec<Vec<String>>,
+    // max_depth cut something off, warned about once per walk
+    depth_cut: bool,
+}
+
+impl Walk<'_> {
+    fn warn(&mut self, msg: String) {
+        self.resolver.warn(msg.clone());
+        self.warnings.push(msg);
+    }
+
+    // relative entries (after $ORIGIN expansion) are taken from base_dir
+    // when set, not from whatever the process cwd is
+    fn object_paths(&self, path: &Path, obj: &ElfObject) -> Vec<SearchDir> {
+        let base_dir = self.resolver.options.base_dir.as_deref();
+        let sysroot = self.resolver.options.sysroot.as_deref();
+        let tag = |entries: &[String], source| {
+            let dirs = entries
+                .iter()
+                .flat_map(|s| s.split(':'))
+                .filter(|s| !s.is_empty())
+                .filter(|s| has_origin(path) || !s.contains("ORIGIN"))
+                .map(|s| {
+                    let dir = resolve_origin(path, s, self.arch, self.machine);
+                    // $ORIGIN is already a path inside the sysroot
+                    let dir = if s.starts_with("$ORIGIN") || s.starts_with("${ORIGIN}") {
+                        dir
+                    } else {
+                        in_sysroot(sysroot, &dir)
+                    };
+                    match base_dir {
+                        Some(base) if dir.is_relative() => base.join(dir),
+                        _ => dir,
+                    }
+                });
+            tagged(dirs, source)
+        };
+        if !obj.runpaths.is_empty() {
+            return tag(&obj.runpaths, SearchSource::Runpath);
+        }
+        tag(&obj.rpaths, SearchSource::Rpath)
+    }
+
+    // For secure-exec binaries glibc only trusts the system directories, any
+    // other RPATH/RUNPATH entry is skipped
+    fn trusted_paths(&mut self, paths: Vec<SearchDir>) -> Vec<SearchDir> {
+        if !self.secure {
+            return paths;
+        }
+        let canonical = |p: &Path| fs::canonicalize(p).unwrap_or(p.to_path_buf());
+        let trusted: HashSet<PathBuf> = self.dirs.iter().map(|d| canonical(&d.path)).collect();
+        let (kept, ignored): (Vec<_>, Vec<_>) = paths
+            .into_iter()
+            .partition(|d| trusted.contains(&canonical(&d.path)));
+        for dir in ignored {
+            if !self.ignored_dirs.contains(&dir) {
+                self.ignored_dirs.push(dir);
+            }
+        }
+        kept
+    }
+
+    fn push_interp(&mut self, interp: &str) {
+        let interp_path = in_sysroot(self.resolver.options.sysroot.as_deref(), Path::new(interp));
+
+        let resolved_interp = match &self.resolver.options.sysroot {
+            Some(sysroot) => follow_in_sysroot(sysroot, &interp_path),
+            None if interp_path.exists() => {
+                interp_path.canonicalize().unwrap_or(interp_path.clone())
+            }
+            None => interp_path.clone(),
+        };
+
+        let lib_name = interp_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(interp)
+            .to_string();
+        if !self.seen_libs.insert(lib_name.clone()) {
+            return;
+        }
+
+        let kind = resolved_interp.exists().then_some(LibKind::Shared);
+        self.stop = self.target.as_ref() == Some(&lib_name);
+        let status = match kind {
+            Some(_) => DepStatus::Found(resolved_interp.clone()),
+            None => DepStatus::NotFound,
+        };
+        self.push(
+            status,
+            ResolvedLib {
+                name: lib_name,
+                dir: resolved_interp.parent().map(Path::to_path_buf),
+                path: Some(resolved_interp),
+                kind,
+                depth: 1,
+                source: Some(SearchSource::Interp),
+                ..Default::default()
+            },
+        );
+    }
+
+    fn push(&mut self, status: DepStatus, mut lib: ResolvedLib) {
+        if lib.real_path.is_none() {
+            lib.real_path = lib.path.as_ref().and_then(|p| fs::canonicalize(p).ok());
+        }
+        if let Some(hook) = &self.resolver.options.on_dep {
+            hook.call(&DepEvent {
+                name: lib.name.clone(),
+                status: status.clone(),
+                path: lib.path.clone(),
+                depth: lib.depth,
+            });
+        }
+        if self.resolver.options.fail_fast && !status.is_resolved() {
+            self.stop = true;
+            self.failed_at.get_or_insert_with(|| lib.name.clone());
+        }
+        self.res.push((lib.name.clone(), status));
+        self.libs.push(lib);
+    }
+
+    fn script_members(&self, script: &Path, paths: &[SearchDir], nesting: usize) -> Vec<PathBuf> {
+        let mut members = Vec::new();
+        let mut local = vec![SearchDir {
+            path: script.parent().unwrap_or(Path::new("/")).to_path_buf(),
+            source: SearchSource::BinDir,
+        }];
+        local.extend_from_slice(paths);
+        let priority = self.resolver.options.ld_library_path_priority;
+
+        for input in ld_script_inputs(script) {
+            let found = if let Some(short) = input.strip_prefix("-l") {
+                find_library(&format!("lib{}.so", short), &self.dirs, &local, priority)
+                    .map(|(p, _)| p)
+            } else if input.starts_with('/') {
+                Some(PathBuf::from(&input)).filter(|p| p.exists())
+            } else {
+                find_library(&input, &self.dirs, &local, priority).map(|(p, _)| p)
+            };
+
+            let Some(found) = found else { continue };
+            match sniff_lib_kind(&found) {
+                LibKind::LinkerScript => {
+                    if nesting < 8 {
+                        members.extend(self.script_members(&found, paths, nesting + 1));
+                    }
+                }
+                LibKind::Archive => {}
+                _ => members.push(found),
+            }
+        }
+        members
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn resolve_script(
+        &mut self,
+        dep: &str,
+        script: &Path,
+        script_dir: &SearchDir,
+        paths: &[SearchDir],
+        e_machine: u16,
+        d: usize,
+    ) -> bool {
+        let mut resolved = false;
+
+        for member in self.script_members(script, paths, 0) {
+            if self.stop {
+                break;
+            }
+            let Ok(m_obj) = self.resolver.object(&member) else {
+                continue;
+            };
+
+            if m_obj.e_machine != e_machine {
+                continue;
+            }
+            if !is_same_arch(self.arch, &m_obj) {
+                continue;
+            }
+
+            let name = if resolved {
+                let name = member
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                if !self.seen_libs.insert(name.clone()) {
+                    continue;
+                }
+                name
+            } else {
+                dep.to_string()
+            };
+            resolved = true;
+
+            if let Err(e) = self.inner(&member, &m_obj, d + 1) {
+                self.warn(format!("Recursive error {:?}: {:?}", member, e));
+            }
+            self.push(
+                DepStatus::Found(member.clone()),
+                ResolvedLib {
+                    name,
+                    path: Some(member),
+                    kind: Some(m_obj.kind),
+                    script: Some(script.to_path_buf()),
+                    depth: d + 1,
+                    dir: Some(script_dir.path.clone()),
+                    source: Some(script_dir.source),
+                    executable_stack: m_obj.exec_stack,
+                    uses_static_tls: m_obj.static_tls,
+                    ..Default::default()
+                },
+            );
+        }
+        resolved
+    }
+
+    // Breadth first over NEEDED entries like ld.so does, entries the BFS
+    // can't reach (e.g. extra linker script members) keep their relative order
+    // at the end.
+    // entries of libs in loader order, each with the object whose NEEDED
+    // entry made ld.so load it
+    fn bfs_order(&self, root_path: &Path, root: &ElfObject) -> Vec<(usize, Option<PathBuf>)> {
+        let index: HashMap<&str, usize> = self
+            .libs
+            .iter()
+            .enumerate()
+            .map(|(i, lib)| (lib.name.as_str(), i))
+            .collect();
+
+        let mut order: Vec<_> = (0..self.libs.len())
+            .filter(|&i| self.libs[i].source == Some(SearchSource::Interp))
+            .map(|i| (i, None))
+            .collect();
+        let mut emitted: HashSet<usize> = order.iter().map(|(i, _)| *i).collect();
+        let mut queue = VecDeque::from([(root_path.to_path_buf(), root.libraries.clone())]);
+
+        while let Some((requester, needed)) = queue.pop_front() {
+            for name in &needed {
+                let Some(&i) = index.get(name.as_str()) else {
+                    continue;
+                };
+                if !emitted.insert(i) {
+                    continue;
+                }
+                order.push((i, Some(requester.clone())));
+                if self.res[i].1.is_resolved()
+                    && let Some(path) = &self.libs[i].path
+                    && let Ok(obj) = self.resolver.object(path)
+                {
+                    queue.push_back((path.clone(), obj.libraries.clone()));
+                }
+            }
+        }
+        order.extend(
+            (0..self.libs.len())
+                .filter(|i| !emitted.contains(i))
+                .map(|i| (i, None)),
+        );
+        order
+    }
+
+    // The file ld.so really loads for a soname is the one the first
+    // requester in load order finds, which is not necessarily the one the
+    // depth first walk recorded.
+    fn rank_loads(&mut self, root_path: &Path, root: &ElfObject) {
+        let order = self.bfs_order(root_path, root);
+        self.load_rank = vec![0; self.libs.len()];
+        for (rank, (i, requester)) in order.into_iter().enumerate() {
+            self.load_rank[i] = rank;
+            let name = self.libs[i].name.clone();
+            let Some(requester) = requester else { continue };
+            if !self.alternates.iter().any(|(n, _)| *n == name) {
+                continue;
+            }
+            let paths = if requester == root_path {
+                self.object_paths(root_path, root)
+            } else {
+                match self.resolver.object(&requester) {
+                    Ok(obj) => self.object_paths(&requester, &obj),
+                    Err(_) => continue,
+                }
+            };
+            let paths = self.trusted_paths(paths);
+            if let Some((loaded, _)) = self.find(&name, &paths) {
+                self.loaded.insert(name, loaded);
+            }
+        }
+    }
+
+    fn load_order(&mut self, root_path: &Path, root: &ElfObject) {
+        let order: Vec<_> = self
+            .bfs_order(root_path, root)
+            .into_iter()
+            .map(|(i, _)| i)
+            .collect();
+        let mut res: Vec<_> = self.res.drain(..).map(Some).collect();
+        let mut libs: Vec<_> = self.libs.drain(..).map(Some).collect();
+        for i in order {
+            self.res.extend(res[i].take());
+            self.libs.extend(libs[i].take());
+        }
+    }
+
+    // the chain from `pos` down to the object that needs `name` again
+    fn note_cycle(&mut self, pos: usize, name: String) {
+        let mut cycle: Vec<String> = self.chain[pos..].iter().map(|(n, _)| n.clone()).collect();
+        cycle.push(name);
+        if !self.cycles.contains(&cycle) {
+            self.warn(format!("Warning: dependency cycle {}", cycle.join(" -> ")));
+            self.cycles.push(cycle);
+        }
+    }
+
+    fn check_duplicate_needed(&mut self, path: &Path, obj: &ElfObject) {
+        let mut seen = HashSet::new();
+        for dep in &obj.libraries {
+            if !seen.insert(dep) {
+                self.warn(format!(
+                    "Warning: {:?} lists {} twice in DT_NEEDED",
+                    path, dep
+                ));
+                if !self.duplicate_needed.contains(dep) {
+                    self.duplicate_needed.push(dep.clone());
+                }
+            }
+        }
+    }
+
+    // A NEEDED entry with a slash is opened as is, without any search. ld.so
+    // takes relative ones from its cwd; here that is base_dir, falling back
+    // to the directory of the object declaring it.
+    fn needed_path(&self, obj_path: &Path, dep: &str) -> PathBuf {
+        let options = &self.resolver.options;
+        let dep = Path::new(dep);
+        let path = if dep.is_absolute() {
+            in_sysroot(options.sysroot.as_deref(), dep)
+        } else {
+            let base = options
+                .base_dir
+                .as_deref()
+                .unwrap_or(obj_path.parent().unwrap_or(Path::new("/")));
+            base.join(dep)
+        };
+        // drops the "./" components
+        path.components().collect()
+    }
+
+    fn find(&self, lib: &str, paths: &[SearchDir]) -> Option<(PathBuf, SearchDir)> {
+        self.resolver.find_in(lib, &self.dirs, paths)
+    }
+
+    // Looks up and parses the siblings not seen yet on the rayon pool, the
+    // serial walk then takes the lookups from here and the objects from the
+    // cache. Walks that may stop early don't read ahead.
+    #[cfg(feature = "parallel")]
+    fn prefetch(&self, obj: &ElfObject, paths: &[SearchDir]) -> HashMap<String, Lookup> {
+        use rayon::prelude::*;
+
+        let options = &self.resolver.options;
+        if !options.parallel
+            || options.fail_fast
+            || options.dir_contents.is_some()
+            || self.target.is_some()
+            || obj.libraries.len() < 2
+        {
+            return HashMap::new();
+        }
+        let (resolver, dirs, seen) = (self.resolver, &self.dirs, &self.seen_libs);
+        let wanted: HashSet<&String> = obj
+            .libraries
+            .iter()
+            .filter(|dep| !dep.contains('/') && !seen.contains(*dep))
+            .collect();
+        wanted
+            .into_par_iter()
+            .map(|dep| {
+                let found = resolver.find_in(dep, dirs, paths);
+                if let Some((path, dir)) = &found
+                    && dir.source != SearchSource::Virtual
+                {
+                    let _ = resolver.object(path);
+                }
+                (dep.clone(), found)
+            })
+            .collect()
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    fn prefetch(&self, _obj: &ElfObject, _paths: &[SearchDir]) -> HashMap<String, Lookup> {
+        HashMap::new()
+    }
+
+    fn note_alternate(&mut self, dep: &str, paths: &[SearchDir]) {
+        let Some((alt, _)) = self.find(dep, paths) else {
+            return;
+        };
+        let same_file = |a: &Path, b: &Path| {
+            a == b || fs::canonicalize(a).is_ok_and(|a| fs::canonicalize(b).is_ok_and(|b| a == b))
+        };
+        let first = self.libs.iter().find(|lib| lib.name == dep);
+        if let Some(first) = first.and_then(|lib| lib.path.as_ref())
+            && !same_file(first, &alt)
+        {
+            self.alternates.push((dep.to_string(), alt));
+        }
+    }
+
+    fn inner(&mut self, path: &Path, obj: &ElfObject, d: usize) -> io::Result<()> {
+        if d >= self.resolver.options.max_depth {
+            if !obj.libraries.is_empty() && !self.depth_cut {
+                self.depth_cut = true;
+                self.warn(format!("Warning: max recursion depth at {:?}", path));
+            }
+            return Ok(());
+        }
+
+        let name = path.file_name().map_or_else(
+            || path.display().to_string(),
+            |n| n.to_string_lossy().into_owned(),
+        );
+        let key = match fs::metadata(path) {
+            Ok(meta) => {
+                let key = (meta.dev(), meta.ino());
+                if !self.visited.insert(key) {
+                    // reached again under another name, e.g. a library
+                    // needing the root that is itself a library
+                    if let Some(pos) = self.chain.iter().position(|(_, k)| *k == Some(key)) {
+                        self.note_cycle(pos, name);
+                    }
+                    return Ok(());
+                }
+                Some(key)
+            }
+            // a root given as bytes has no file behind it
+            Err(_) if d == 0 && path.ends_with(IN_MEMORY) => None,
+            Err(_) => {
+                self.warn(format!("Error access {:?}", path));
+                return Ok(());
+            }
+        };
+        self.chain.push((name, key));
+
+        self.check_duplicate_needed(path, obj);
+        let paths = self.object_paths(path, obj);
+        let paths = self.trusted_paths(paths);
+
+        for dir in paths.iter().map(|d| &d.path) {
+            if !dir.exists()
+                && let Ok(target) = fs::read_link(dir)
+            {
+                self.warn(format!(
+                    "Warning: RPATH entry {:?} of {:?} is a dangling symlink to {:?}",
+                    dir, path, target
+                ));
+            }
+            if !dir.is_dir() && !self.invalid_rpath_dirs.contains(dir) {
+                self.invalid_rpath_dirs.push(dir.clone());
+            }
+        }
+
+        let mut prefetched = self.prefetch(obj, &paths);
+
+        for dep in &obj.libraries {
+            if !self.seen_libs.insert(dep.clone()) {
+                let base = Path::new(dep).file_name().unwrap_or_default();
+                if let Some(pos) = self.chain.iter().position(|(n, _)| base == n.as_str()) {
+                    self.note_cycle(pos, dep.clone());
+                }
+                // search dirs are shared, only rpaths can pick a different file
+                if !paths.is_empty() && !dep.contains('/') {
+                    self.note_alternate(dep, &paths);
+                }
+                continue;
+            }
+
+            if self.target.as_ref() == Some(dep) {
+                self.stop = true;
+            }
+            if self.stop {
+                break;
+            }
+
+            let direct = dep.contains('/').then(|| self.needed_path(path, dep));
+            let mut names = vec![dep.clone()];
+            let mut found = match &direct {
+                Some(candidate) => candidate.exists().then(|| {
+                    let dir = SearchDir {
+                        path: candidate.parent().unwrap_or(Path::new("/")).to_path_buf(),
+                        source: SearchSource::Direct,
+                    };
+                    (candidate.clone(), dir)
+                }),
+                None => match prefetched.remove(dep) {
+                    Some(found) => found,
+                    None => self.find(dep, &paths),
+                },
+            };
+            if found.is_none()
+                && direct.is_none()
+                && self.resolver.options.name_arch_suffix
+                && let Some(alt) = arch_suffixed_name(dep, self.arch)
+            {
+                found = self.find(&alt, &paths);
+                names.push(alt);
+            }
+            let Some((found, dir)) = found else {
+                let assumed = self.resolver.options.assume_present.contains(dep);
+                let attempted = if !self.resolver.options.record_attempts || assumed {
+                    Vec::new()
+                } else if let Some(candidate) = direct {
+                    vec![candidate]
+                } else {
+                    let priority = self.resolver.options.ld_library_path_priority;
+                    names
+                        .iter()
+                        .flat_map(|name| {
+                            search_order(&self.dirs, &paths, priority)
+                                .map(move |d| d.path.join(name))
+                        })
+                        .collect()
+                };
+                self.push(
+                    if assumed {
+                        DepStatus::Assumed
+                    } else {
+                        DepStatus::NotFound
+                    },
+                    ResolvedLib {
+                        name: dep.clone(),
+                        depth: d + 1,
+                        attempted,
+                        ..Default::default()
+                    },
+                );
+                continue;
+            };
+
+            if self.resolver.options.dir_contents.is_some() || dir.source == SearchSource::Virtual {
+                self.push(
+                    DepStatus::Found(found.clone()),
+                    ResolvedLib {
+                        name: dep.clone(),
+                        path: Some(found),
+                        depth: d + 1,
+                        dir: Some(dir.path),
+                        source: Some(dir.source),
+                        ..Default::default()
+                    },
+                );
+                continue;
+            }
+
+            let s_obj = match self.resolver.object(&found) {
+                Ok(s_obj) => {
+                    // a library for another CPU can sit in a multilib dir
+                    if s_obj.e_machine != obj.e_machine {
+                        self.push(
+                            DepStatus::MachineMismatch,
+                            ResolvedLib {
+                                name: dep.clone(),
+                                path: Some(found),
+                                kind: Some(s_obj.kind),
+                                depth: d + 1,
+                                dir: Some(dir.path),
+                                source: Some(dir.source),
+                                ..Default::default()
+                            },
+                        );
+                        continue;
+                    }
+                    if !is_same_arch(self.arch, &s_obj) {
+                        self.push(
+                            DepStatus::ArchMismatch,
+                            ResolvedLib {
+                                name: dep.clone(),
+                                path: Some(found),
+                                kind: Some(s_obj.kind),
+                                depth: d + 1,
+                                dir: Some(dir.path),
+                                source: Some(dir.source),
+                                executable_stack: s_obj.exec_stack,
+                                uses_static_tls: s_obj.static_tls,
+                                hash_style: s_obj.hash_style,
+                                dynsym_count: s_obj.dynsym_count,
+                                soname: s_obj.soname.clone(),
+                                ..Default::default()
+                            },
+                        );
+                        continue;
+                    }
+
+                    if let Err(e) = self.inner(&found, &s_obj, d + 1) {
+                        self.warn(format!("Recursive error {:?}: {:?}", found, e));
+                    }
+                    Some(s_obj)
+                }
+                Err(e) if e.kind() == io::ErrorKind::PermissionDenied => {
+                    self.push(
+                        DepStatus::PermissionDenied,
+                        ResolvedLib {
+                            name: dep.clone(),
+                            path: Some(found),
+                            depth: d + 1,
+                            dir: Some(dir.path),
+                            source: Some(dir.source),
+                            ..Default::default()
+                        },
+                    );
+                    continue;
+                }
+                Err(_) => None,
+            };
+            let kind = s_obj
+                .as_ref()
+                .map_or_else(|| sniff_lib_kind(&found), |o| o.kind);
+
+            if kind.is_linker_script()
+                && self.resolver.options.follow_ld_scripts
+                && self.resolve_script(dep, &found, &dir, &paths, obj.e_machine, d)
+            {
+                continue;
+            }
+
+            let status = if kind.is_linker_script() {
+                DepStatus::LinkerScript
+            } else if s_obj.is_none() {
+                DepStatus::ParseError
+            } else {
+                DepStatus::Found(found.clone())
+            };
+            self.push(
+                status,
+                ResolvedLib {
+                    name: dep.clone(),
+                    path: Some(found),
+                    kind: Some(kind),
+                    depth: d + 1,
+                    dir: Some(dir.path),
+                    source: Some(dir.source),
+                    executable_stack: s_obj.as_ref().is_some_and(|o| o.exec_stack),
+                    uses_static_tls: s_obj.as_ref().is_some_and(|o| o.static_tls),
+                    hash_style: s_obj.as_ref().map_or(HashStyle::None, |o| o.hash_style),
+                    dynsym_count: s_obj.as_ref().map_or(0, |o| o.dynsym_count),
+                    soname: s_obj.as_ref().and_then(|o| o.soname.clone()),
+                    ..Default::default()
+                },
+            );
+        }
+
+        self.chain.pop();
+        Ok(())
+    }
+}
+
+pub fn rldd_rex<P: AsRef<Path> + std::fmt::Debug>(path: P) -> Result<RlddRexInfo, RlddError> {
+    Resolver::new().rldd_rex(path)
+}
+
+pub fn rldd_rex_from_bytes(data: &[u8], origin: Option<&Path>) -> Result<RlddRexInfo, RlddError> {
+    Resolver::new().rldd_rex_from_bytes(data, origin)
+}
+
+pub fn rldd_rex_lenient<P: AsRef<Path> + std::fmt::Debug>(path: P) -> io::Result<RlddRexInfo> {
+    Resolver::new().rldd_rex_lenient(path)
+}
+
+pub fn depends_on<P: AsRef<Path> + std::fmt::Debug>(path: P, soname: &str) -> bool {
+    Resolver::new().depends_on(path, soname)
+}
+
+// PT_INTERP contents as stored, only the terminating NUL is dropped so
+// padding left behind by patchelf-style edits stays visible
+pub fn raw_interpreter<P: AsRef<Path>>(path: P) -> Option<String> {
+    let map = open_and_map(&path).ok()?;
+    let raw = read_interp(&map)?;
+    let raw = raw.strip_suffix(b"\0").unwrap_or(raw);
+    Some(String::from_utf8_lossy(raw).into_owned())
+}
+
+// PT_INTERP straight from the program headers, nothing else gets parsed
+fn read_interp(data: &[u8]) -> Option<&[u8]> {
+    let header = Elf::parse_header(data).ok()?;
+    let ctx = goblin::container::Ctx::new(header.container().ok()?, header.endianness().ok()?);
+    let phdrs =
+        ProgramHeader::parse(data, header.e_phoff as usize, header.e_phnum as usize, ctx).ok()?;
+    let ph = phdrs.iter().find(|ph| ph.p_type == PT_INTERP)?;
+    let start = ph.p_offset as usize;
+    data.get(start..start.checked_add(ph.p_filesz as usize)?)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InterpreterStatus {
+    pub path: Option<PathBuf>,
+    pub exists: bool,
+    pub family: Option<LibcFamily>,
+}
+
+pub fn interpreter_status<P: AsRef<Path>>(path: P) -> InterpreterStatus {
+    let interp = open_and_map(&path).ok().and_then(|map| {
+        let raw = read_interp(&map)?;
+        let raw = raw.split(|&b| b == 0).next()?;
+        Some(PathBuf::from(String::from_utf8_lossy(raw).into_owned()))
+    });
+    let family = interp.as_ref().and_then(|p| {
+        let name = p.file_name()?.to_string_lossy();
+        if name.contains("musl") {
+            Some(LibcFamily::Musl)
+        } else if name.starts_with("ld-linux") || name.starts_with("ld64.so") {
+            Some(LibcFamily::Glibc)
+        } else {
+            None
+        }
+    });
+    InterpreterStatus {
+        exists: interp.as_ref().is_some_and(|p| p.exists()),
+        path: interp,
+        family,
+    }
+}
+
+pub fn missing_from_base<P: AsRef<Path> + std::fmt::Debug>(
+    path: P,
+    base_files: &HashSet<PathBuf>,
+) -> Vec<ResolvedLib> {
+    Resolver::new().missing_from_base(path, base_files)
+}
+
+pub fn resolve_many(
+    names: &[String],
+    arch: ElfArch,
+    machine: ElfMachine,
+    options: RlddOptions,
+) -> Vec<(String, DepStatus)> {
+    Resolver::with_options(options).resolve_many(names, arch, machine)
+}
+
+pub fn same_closure<A: AsRef<Path>, B: AsRef<Path>>(a: A, b: B) -> bool {
+    Resolver::new().same_closure(a, b)
+}
+
+// Plain query, a file that cannot be opened or parsed is reported as false
+pub fn is_pie_executable<P: AsRef<Path> + std::fmt::Debug>(path: P) -> bool {
+    let Ok(map) = open_and_map(&path) else {
+        return false;
+    };
+    let mut scratch = Vec::new();
+    parse_elf(&map, &mut scratch).is_ok_and(|elf| get_elf_type(&elf).is_pie())
+}
+
+// Err lists every soname that did not resolve, ready for use in a #[test].
+// Libraries only the bin dir heuristic found count as unresolved.
+pub fn assert_deps_resolved<P: AsRef<Path> + std::fmt::Debug>(path: P) -> Result<(), Vec<String>> {
+    let info = rldd_rex(path).map_err(|e| vec![e.to_string()])?;
+    let unresolved: Vec<String> = info
+        .libs
+        .iter()
+        .zip(info.deps)
+        .filter(|(lib, (_, status))| !status.is_resolved() || lib.is_heuristic_only())
+        .map(|(_, (name, _))| name)
+        .collect();
+    if unresolved.is_empty() {
+        Ok(())
+    } else {
+        Err(unresolved)
+    }
+}
+
+pub fn closure_has_exec_stack<P: AsRef<Path> + std::fmt::Debug>(path: P) -> bool {
+    rldd_rex(path).is_ok_and(|info| info.closure_has_exec_stack())
+}
+
+pub fn search_dirs_for<P: AsRef<Path>>(path: P) -> io::Result<Vec<PathBuf>> {
+    Resolver::new().search_dirs_for(path)
+}
+
+pub fn rldd_rex_with_sysroot<P: AsRef<Path> + std::fmt::Debug>(
+    path: P,
+    sysroot: &Path,
+) -> Result<RlddRexInfo, RlddError> {
+    let options = RlddOptions {
+        sysroot: Some(sysroot.to_path_buf()),
+        ..Default::default()
+    };
+    rldd_rex_with_options(path, options)
+}
+
+pub fn rldd_rex_with_paths<P: AsRef<Path> + std::fmt::Debug>(
+    path: P,
+    extra_dirs: &[PathBuf],
+    position: ExtraDirsPosition,
+) -> Result<RlddRexInfo, RlddError> {
+    let options = RlddOptions {
+        extra_dirs: extra_dirs.to_vec(),
+        extra_dirs_position: position,
+        ..Default::default()
+    };
+    rldd_rex_with_options(path, options)
+}
+
+pub fn rldd_rex_with_options<P: AsRef<Path> + std::fmt::Debug>(
+    path: P,
+    options: RlddOptions,
+) -> Result<RlddRexInfo, RlddError> {
+    Resolver::with_options(options).rldd_rex(path)
+}
+
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,103 @@
+use crate::{
+    ElfArch, ElfMachine, ElfType, PackerKind, Relro, Resolver, RlddError, RlddRexInfo,
+    check_header, detect_packer, get_elf_type, has_exec_stack, machine_from_e_machine,
+    open_and_map, relro, without_section_headers,
+};
+use goblin::elf::Elf;
+use memmap2::Mmap;
+use std::path::{Path, PathBuf};
+
+// A binary mapped and parsed once, for callers asking several questions
+// about the same file.
+pub struct ParsedBinary {
+    // Borrows from `map` or `_patched`, both of which stay put for as long as
+    // the struct lives: the mapping never moves and `_patched` is never
+    // touched again. Declared first so it is dropped before them.
+    elf: Elf<'static>,
+    _patched: Option<Vec<u8>>,
+    map: Mmap,
+    path: PathBuf,
+}
+
+impl ParsedBinary {
+    // fails the same way rldd_rex does for the root
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, RlddError> {
+        let path = path.as_ref().to_path_buf();
+        let map = open_and_map(&path)?;
+        if !map.starts_with(b"\x7fELF") {
+            return Err(RlddError::NotElf);
+        }
+        // SAFETY: the slice points into the mapping, which is owned by the
+        // returned value and outlives `elf`
+        let data: &'static [u8] = unsafe { std::slice::from_raw_parts(map.as_ptr(), map.len()) };
+        let invalid = |e: goblin::error::Error| RlddError::ParseFailed(e.to_string());
+
+        let (elf, patched) = match Elf::parse(data) {
+            Ok(elf) => (elf, None),
+            Err(e) => {
+                let patched = without_section_headers(data).ok_or_else(|| invalid(e))?;
+                // SAFETY: the Vec's heap buffer does not move when the Vec
+                // does and is never written to after this point
+                let data: &'static [u8] =
+                    unsafe { std::slice::from_raw_parts(patched.as_ptr(), patched.len()) };
+                (Elf::parse(data).map_err(invalid)?, Some(patched))
+            }
+        };
+        check_header(&elf)?;
+
+        Ok(ParsedBinary {
+            elf,
+            _patched: patched,
+            map,
+            path,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn arch(&self) -> ElfArch {
+        [ElfArch::Elf32, ElfArch::Elf64][self.elf.is_64 as usize]
+    }
+
+    pub fn machine(&self) -> ElfMachine {
+        machine_from_e_machine(self.elf.header.e_machine, self.arch())
+    }
+
+    pub fn elf_type(&self) -> ElfType {
+        get_elf_type(&self.elf)
+    }
+
+    pub fn is_pie(&self) -> bool {
+        self.elf_type().is_pie()
+    }
+
+    pub fn interpreter(&self) -> Option<&str> {
+        self.elf.interpreter
+    }
+
+    pub fn needed(&self) -> &[&str] {
+        &self.elf.libraries
+    }
+
+    pub fn executable_stack(&self) -> bool {
+        has_exec_stack(&self.elf)
+    }
+
+    pub fn relro(&self) -> (Relro, u64) {
+        relro(&self.elf)
+    }
+
+    pub fn packed(&self) -> Option<PackerKind> {
+        detect_packer(&self.elf, &self.map)
+    }
+
+    pub fn dependencies(&self) -> Result<RlddRexInfo, RlddError> {
+        self.dependencies_with(&Resolver::new())
+    }
+
+    pub fn dependencies_with(&self, resolver: &Resolver) -> Result<RlddRexInfo, RlddError> {
+        Ok(resolver.info_for(&self.path, &self.elf, &self.map)?)
+    }
+}
@@ -0,0 +1,75 @@
+// `RlddRexInfo::deps` as a list of {name, status, path} objects
+use crate::DepStatus;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::path::PathBuf;
+
+#[derive(Serialize, Deserialize)]
+struct DepEntry {
+    name: String,
+    status: String,
+    path: Option<PathBuf>,
+}
+
+fn tag(status: &DepStatus) -> &'static str {
+    match status {
+        DepStatus::Found(_) => "Found",
+        DepStatus::NotFound => "NotFound",
+        DepStatus::ArchMismatch => "ArchMismatch",
+        DepStatus::MachineMismatch => "MachineMismatch",
+        DepStatus::LinkerScript => "LinkerScript",
+        DepStatus::PermissionDenied => "PermissionDenied",
+        DepStatus::ParseError => "ParseError",
+        DepStatus::Assumed => "Assumed",
+    }
+}
+
+pub fn serialize<S: Serializer>(deps: &[(String, DepStatus)], s: S) -> Result<S::Ok, S::Error> {
+    let entries: Vec<DepEntry> = deps
+        .iter()
+        .map(|(name, status)| DepEntry {
+            name: name.clone(),
+            status: tag(status).to_string(),
+            path: match status {
+                DepStatus::Found(path) => Some(path.clone()),
+                _ => None,
+            },
+        })
+        .collect();
+    entries.serialize(s)
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<(String, DepStatus)>, D::Error> {
+    Vec::<DepEntry>::deserialize(d)?
+        .into_iter()
+        .map(|entry| {
+            let status = match (entry.status.as_str(), entry.path) {
+                ("Found", Some(path)) => DepStatus::Found(path),
+                ("Found", None) => return Err(D::Error::missing_field("path")),
+                ("NotFound", _) => DepStatus::NotFound,
+                ("ArchMismatch", _) => DepStatus::ArchMismatch,
+                ("MachineMismatch", _) => DepStatus::MachineMismatch,
+                ("LinkerScript", _) => DepStatus::LinkerScript,
+                ("PermissionDenied", _) => DepStatus::PermissionDenied,
+                ("ParseError", _) => DepStatus::ParseError,
+                ("Assumed", _) => DepStatus::Assumed,
+                (other, _) => {
+                    return Err(D::Error::unknown_variant(
+                        other,
+                        &[
+                            "Found",
+                            "NotFound",
+                            "ArchMismatch",
+                            "MachineMismatch",
+                            "LinkerScript",
+                            "PermissionDenied",
+                            "ParseError",
+                            "Assumed",
+                        ],
+                    ));
+                }
+            };
+            Ok((entry.name, status))
+        })
+        .collect()
+}
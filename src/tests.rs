@@ -1,5 +1,9 @@
 use super::*;
 
+mod fixture;
+
+use fixture::*;
+
 #[test]
 fn test_verbose_deps() -> Result<(), Box<dyn std::error::Error>> {
     let select = 1;
@@ -31,6 +35,7 @@ fn test_verbose_deps() -> Result<(), Box<dyn std::error::Error>> {
         ElfType::Dynamic => println!("Dynamic: depends on shared libs"),
         ElfType::Static => println!("Static: all libs included"),
         ElfType::Pie => println!("PIE: position independent executable"),
+        ElfType::Relocatable => println!("Relocatable: object file"),
         ElfType::Invalid => println!("Invalid ELF"),
     }
 
@@ -43,18 +48,20 @@ fn test_verbose_deps() -> Result<(), Box<dyn std::error::Error>> {
     println!("\nRecursive dependencies for {}:", path);
     for (i, (lib, path_or_status)) in deps.deps.iter().enumerate() {
         let num = i + 1;
-        let colorized = if path_or_status == "not found" || path_or_status == "arch mismatch" {
-            format!("\x1b[1;31m{}\x1b[0m", path_or_status)
-        } else if path_or_status.starts_with("/lib") || path_or_status.starts_with("/usr") {
-            format!("\x1b[1;32m{}\x1b[0m", path_or_status)
-        } else {
-            format!("\x1b[1;33m{}\x1b[0m", path_or_status)
+        let colorized = match path_or_status {
+            DepStatus::Found(path) if path.starts_with("/lib") || path.starts_with("/usr") => {
+                format!("\x1b[1;32m{}\x1b[0m", path_or_status)
+            }
+            DepStatus::NotFound | DepStatus::ArchMismatch => {
+                format!("\x1b[1;31m{}\x1b[0m", path_or_status)
+            }
+            _ => format!("\x1b[1;33m{}\x1b[0m", path_or_status),
         };
 
         println!("{num}. {} => {}", lib, colorized);
 
-        match path_or_status.as_str() {
-            "not found" | "arch mismatch" => dnf += 1,
+        match path_or_status {
+            DepStatus::NotFound | DepStatus::ArchMismatch => dnf += 1,
             _ => df += 1,
         }
     }
@@ -62,3 +69,3276 @@ fn test_verbose_deps() -> Result<(), Box<dyn std::error::Error>> {
     println!("Dependencies not found: {dnf}\n");
     Ok(())
 }
+
+#[test]
+fn test_batch_scan_dir_visits_each_elf_once() {
+    let tree = TempTree::new("batch");
+    let mut expected = vec![
+        tree.write_elf("bin/app", &ElfFixture::exe().needed("libbatch_a.so")),
+        tree.write_elf(
+            "lib/libbatch_a.so",
+            &ElfFixture::lib().needed("libbatch_b.so"),
+        ),
+        tree.write_elf("lib/deep/er/libbatch_b.so", &ElfFixture::lib()),
+        tree.write_elf("usr/bin/tool", &ElfFixture::exe()),
+    ];
+    tree.write("etc/readme.txt", b"not an elf");
+    std::os::unix::fs::symlink(&expected[0], tree.join("bin/app-link")).unwrap();
+
+    let mut seen: Vec<_> = batch_scan_dir(tree.path(), 3)
+        .map(|(path, info)| {
+            assert!(info.unwrap().elf_type.is_valid());
+            path
+        })
+        .collect();
+
+    seen.sort();
+    expected.sort();
+    assert_eq!(seen, expected);
+}
+
+#[test]
+fn test_linker_script_is_flagged() {
+    let tree = TempTree::new("ldscript");
+    let bin = tree.write_elf(
+        "app",
+        &ElfFixture::exe()
+            .needed("libscript_fixture.so")
+            .needed("libarchive_fixture.so"),
+    );
+    tree.write(
+        "libscript_fixture.so",
+        b"/* GNU ld script\n   Use the shared library */\nGROUP ( /nonexistent/libc.so.6 )\n",
+    );
+    tree.write("libarchive_fixture.so", b"!<arch>\n");
+
+    let info = rldd_rex(&bin).unwrap();
+    let script = info
+        .libs
+        .iter()
+        .find(|l| l.name == "libscript_fixture.so")
+        .unwrap();
+    assert_eq!(script.kind, Some(LibKind::LinkerScript));
+    assert!(
+        info.deps
+            .contains(&("libscript_fixture.so".into(), DepStatus::LinkerScript))
+    );
+
+    let archive = info
+        .libs
+        .iter()
+        .find(|l| l.name == "libarchive_fixture.so")
+        .unwrap();
+    assert_eq!(archive.kind, Some(LibKind::Archive));
+    // there but unusable, so not counted as resolved
+    assert!(
+        info.deps
+            .contains(&("libarchive_fixture.so".into(), DepStatus::ParseError))
+    );
+    let err = assert_deps_resolved(&bin).unwrap_err();
+    assert!(err.contains(&"libarchive_fixture.so".to_string()));
+}
+
+#[test]
+fn test_follow_ld_script_group() {
+    let tree = TempTree::new("ldscript-follow");
+    let bin = tree.write_elf("app", &ElfFixture::exe().needed("libfollow_fixture.so"));
+    let real = tree.write_elf(
+        "lib/libfollow_fixture.so.1",
+        &ElfFixture::lib().needed("libfollow_dep.so.2"),
+    );
+    let dep = tree.write_elf("lib/libfollow_dep.so.2", &ElfFixture::lib());
+    let script = tree.write(
+        "libfollow_fixture.so",
+        format!(
+            "/* GNU ld script */\nOUTPUT_FORMAT(elf64-x86-64)\nGROUP ( {} libfollow_nonshared.a AS_NEEDED ( -lfollow_missing ) )\n",
+            real.display()
+        )
+        .as_bytes(),
+    );
+
+    let info = rldd_rex(&bin).unwrap();
+    let lib = info
+        .libs
+        .iter()
+        .find(|l| l.name == "libfollow_fixture.so")
+        .unwrap();
+    assert_eq!(lib.path.as_deref(), Some(real.as_path()));
+    assert_eq!(lib.kind, Some(LibKind::Shared));
+    assert_eq!(lib.script.as_deref(), Some(script.as_path()));
+
+    let options = RlddOptions {
+        follow_ld_scripts: false,
+        ..Default::default()
+    };
+    let info = rldd_rex_with_options(&bin, options).unwrap();
+    assert!(
+        info.deps
+            .contains(&("libfollow_fixture.so".into(), DepStatus::LinkerScript))
+    );
+    assert!(
+        !info
+            .libs
+            .iter()
+            .any(|l| l.path.as_deref() == Some(dep.as_path()))
+    );
+}
+
+#[test]
+fn test_depends_on() {
+    assert!(depends_on("/bin/ls", "libc.so.6"));
+
+    let tree = TempTree::new("depends-on");
+    let bin = tree.write_elf("app", &ElfFixture::exe().needed("libdep_a.so"));
+    tree.write_elf("libdep_a.so", &ElfFixture::lib().needed("libdep_b.so"));
+    tree.write_elf("libdep_b.so", &ElfFixture::lib());
+
+    assert!(depends_on(&bin, "libdep_b.so"));
+    assert!(!depends_on(&bin, "libdep_missing.so"));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_resolver_disk_cache() {
+    let tree = TempTree::new("disk-cache");
+    let bin = tree.write_elf("app", &ElfFixture::exe().needed("libcache_a.so"));
+    tree.write_elf("libcache_a.so", &ElfFixture::lib().needed("libcache_b.so"));
+    tree.write_elf("libcache_b.so", &ElfFixture::lib());
+    let cache = tree.join("cache.json");
+
+    let cold = Resolver::new();
+    let expected = cold.rldd_rex(&bin).unwrap().deps;
+    assert_eq!(cold.parsed.load(Ordering::Relaxed), 2);
+    cold.save_cache(&cache).unwrap();
+
+    let warm = Resolver::new();
+    warm.load_cache(&cache).unwrap();
+    assert_eq!(warm.rldd_rex(&bin).unwrap().deps, expected);
+    assert_eq!(warm.parsed.load(Ordering::Relaxed), 0);
+
+    tree.write_elf("libcache_b.so", &ElfFixture::lib().soname("libcache_b.so"));
+    let stale = Resolver::new();
+    stale.load_cache(&cache).unwrap();
+    stale.rldd_rex(&bin).unwrap();
+    assert_eq!(stale.parsed.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn test_riscv_float_abi_dirs() {
+    let cases = [
+        (ElfMachine::RiscV64, 0x0, "/usr/lib64/lp64"),
+        (ElfMachine::RiscV64, 0x2, "/usr/lib64/lp64f"),
+        (ElfMachine::RiscV64, 0x4, "/usr/lib64/lp64d"),
+        (ElfMachine::RiscV32, 0x0, "/usr/lib32/ilp32"),
+        (ElfMachine::RiscV32, 0x5, "/usr/lib32/ilp32d"),
+    ];
+    for (machine, e_flags, expected) in cases {
+        let dirs = riscv_abi_dirs(machine, e_flags);
+        assert!(dirs.contains(&PathBuf::from(expected)), "{:?}", dirs);
+    }
+    assert!(riscv_abi_dirs(ElfMachine::X86_64, 0x4).is_empty());
+
+    assert_eq!(
+        machine_from_e_machine(EM_RISCV, ElfArch::Elf32),
+        ElfMachine::RiscV32
+    );
+    assert_eq!(
+        machine_from_e_machine(EM_RISCV, ElfArch::Elf64),
+        ElfMachine::RiscV64
+    );
+}
+
+#[test]
+fn test_on_dep_hook_sees_every_dependency() {
+    let tree = TempTree::new("on-dep");
+    let bin = tree.write_elf(
+        "app",
+        &ElfFixture::exe()
+            .needed("libhook_a.so")
+            .needed("libhook_missing.so"),
+    );
+    tree.write_elf("libhook_a.so", &ElfFixture::lib().needed("libhook_b.so"));
+    tree.write_elf("libhook_b.so", &ElfFixture::lib());
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = events.clone();
+    let options = RlddOptions {
+        on_dep: Some(Hook::new(move |e: &DepEvent| {
+            sink.lock().unwrap().push(e.clone())
+        })),
+        ..Default::default()
+    };
+
+    let info = rldd_rex_with_options(&bin, options).unwrap();
+    let events = events.lock().unwrap();
+    assert_eq!(events.len(), info.deps.len());
+    for (event, (name, status)) in events.iter().zip(&info.deps) {
+        assert_eq!(&event.name, name);
+        assert_eq!(&event.status, status);
+    }
+
+    let depth_of = |n: &str| events.iter().find(|e| e.name == n).unwrap().depth;
+    assert_eq!(depth_of("libhook_a.so"), 1);
+    assert_eq!(depth_of("libhook_b.so"), 2);
+    assert_eq!(depth_of("libhook_missing.so"), 1);
+}
+
+#[test]
+fn test_dangling_rpath_symlink_warns() {
+    let tree = TempTree::new("dangling-rpath");
+    let bin = tree.write_elf(
+        "app",
+        &ElfFixture::exe()
+            .needed("libdangling_fixture.so")
+            .rpath("$ORIGIN/plugins:$ORIGIN/missing"),
+    );
+    std::os::unix::fs::symlink(tree.join("gone"), tree.join("plugins")).unwrap();
+
+    let info = rldd_rex(&bin).unwrap();
+    let warnings: Vec<_> = info
+        .warnings
+        .iter()
+        .filter(|w| w.contains("dangling"))
+        .collect();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("plugins"));
+}
+
+#[test]
+fn test_explain_reports_resolution_decisions() {
+    let tree = TempTree::new("explain");
+    let bin = tree.write_elf(
+        "app",
+        &ElfFixture::exe()
+            .needed("libexplain_a.so")
+            .needed("libexplain_missing.so"),
+    );
+    let lib = tree.write_elf("libexplain_a.so", &ElfFixture::lib());
+
+    let text = explain(&bin);
+    assert!(text.contains("ELF: Elf64 X86_64 Dynamic"), "{}", text);
+    assert!(text.contains("Search path:"));
+    assert!(text.contains("/usr/lib [System]") || text.contains("/usr/lib64 [System]"));
+    assert!(text.contains(&format!(
+        "libexplain_a.so => {} (found in {} [BinDir], depth 1)",
+        lib.display(),
+        tree.path().display()
+    )));
+    assert!(text.contains("libexplain_missing.so => not found (checked"));
+}
+
+#[test]
+fn test_name_arch_suffix() {
+    assert_eq!(
+        arch_suffixed_name("libfoo.so.1", ElfArch::Elf64).as_deref(),
+        Some("libfoo-64.so.1")
+    );
+    assert_eq!(
+        arch_suffixed_name("libfoo", ElfArch::Elf32).as_deref(),
+        Some("libfoo-32")
+    );
+
+    let tree = TempTree::new("arch-suffix");
+    let bin = tree.write_elf("app", &ElfFixture::exe().needed("libsdk_fixture.so"));
+    let lib = tree.write_elf("libsdk_fixture-64.so", &ElfFixture::lib());
+
+    let info = rldd_rex(&bin).unwrap();
+    assert!(
+        info.deps
+            .contains(&("libsdk_fixture.so".into(), DepStatus::NotFound))
+    );
+
+    let options = RlddOptions {
+        name_arch_suffix: true,
+        ..Default::default()
+    };
+    let info = rldd_rex_with_options(&bin, options).unwrap();
+    assert!(
+        info.deps
+            .contains(&("libsdk_fixture.so".into(), DepStatus::Found(lib.clone())))
+    );
+}
+
+#[test]
+fn test_used_dirs() {
+    let tree = TempTree::new("used-dirs");
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("libused_a.so")
+            .needed("libused_b.so")
+            .needed("libused_missing.so")
+            .rpath("$ORIGIN/../a:$ORIGIN/../b:$ORIGIN/../unused"),
+    );
+    tree.write_elf("a/libused_a.so", &ElfFixture::lib());
+    tree.write_elf("b/libused_b.so", &ElfFixture::lib());
+    tree.mkdir("unused");
+
+    let info = rldd_rex(&bin).unwrap();
+    let used = info.used_dirs();
+    assert_eq!(used.len(), 2);
+    assert!(
+        used[0].ends_with("a") && used[1].ends_with("b"),
+        "{:?}",
+        used
+    );
+}
+
+#[test]
+fn test_symbol_providers() {
+    let tree = TempTree::new("symbols");
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("libsym_other.so")
+            .needed("libsym_stub.so")
+            .import("fixture_sym")
+            .import("fixture_missing")
+            .rpath("$ORIGIN/../lib"),
+    );
+    tree.write_elf(
+        "lib/libsym_other.so",
+        &ElfFixture::lib().import("fixture_sym"),
+    );
+    tree.write_elf(
+        "lib/libsym_stub.so",
+        &ElfFixture::lib().export("fixture_sym"),
+    );
+
+    let info = rldd_rex(&bin).unwrap();
+    assert!(info.symbol_providers.is_empty());
+
+    let options = RlddOptions {
+        resolve_symbols: true,
+        ..Default::default()
+    };
+    let info = rldd_rex_with_options(&bin, options).unwrap();
+    assert_eq!(
+        info.symbol_providers,
+        vec![("fixture_sym".to_string(), "libsym_stub.so".to_string())]
+    );
+}
+
+#[test]
+fn test_debug_link() {
+    let tree = TempTree::new("debuglink");
+    let mut link = b"app.debug\0\0\0".to_vec();
+    link.extend_from_slice(&0x1234_5678u32.to_le_bytes());
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe().section(".gnu_debuglink", 1, &link),
+    );
+
+    let info = rldd_rex(&bin).unwrap();
+    assert_eq!(info.debug_link.as_deref(), Some("app.debug"));
+    assert_eq!(info.debug_file, None);
+
+    let debug_root = tree.mkdir("debug");
+    let options = RlddOptions {
+        debug_search_dirs: vec![debug_root.clone()],
+        ..Default::default()
+    };
+    let info = rldd_rex_with_options(&bin, options.clone()).unwrap();
+    assert_eq!(info.debug_file, None);
+
+    let expected = tree.write(
+        &format!("debug{}/app.debug", tree.join("bin").display()),
+        b"",
+    );
+    let info = rldd_rex_with_options(&bin, options).unwrap();
+    assert_eq!(info.debug_file, Some(expected));
+}
+
+#[test]
+fn test_dedup_scope() {
+    let tree = TempTree::new("dedup");
+    let exe = ElfFixture::exe()
+        .needed("libdedup_c.so")
+        .rpath("$ORIGIN/../lib");
+    let first = tree.write_elf("bin/first", &exe);
+    let second = tree.write_elf("bin/second", &exe);
+    tree.write_elf("lib/libdedup_c.so", &ElfFixture::lib());
+    let has_lib = |info: &RlddRexInfo| info.deps.iter().any(|(name, _)| name == "libdedup_c.so");
+
+    let resolver = Resolver::new();
+    assert!(has_lib(&resolver.rldd_rex(&first).unwrap()));
+    assert!(has_lib(&resolver.rldd_rex(&second).unwrap()));
+
+    let resolver = Resolver::with_options(RlddOptions {
+        dedup_scope: DedupScope::Shared,
+        ..Default::default()
+    });
+    assert!(has_lib(&resolver.rldd_rex(&first).unwrap()));
+    assert!(!has_lib(&resolver.rldd_rex(&second).unwrap()));
+}
+
+#[test]
+fn test_relocatable_object() {
+    let tree = TempTree::new("reloc");
+    let obj = tree.write_elf(
+        "obj/main.o",
+        &ElfFixture::object()
+            .import("fixture_extern")
+            .export("fixture_local"),
+    );
+
+    let info = rldd_rex(&obj).unwrap();
+    assert_eq!(info.elf_type, ElfType::Relocatable);
+    assert!(info.deps.is_empty());
+    assert_eq!(info.undefined_symbols, vec!["fixture_extern".to_string()]);
+}
+
+#[test]
+fn test_ld_path_separator() {
+    let dirs = split_ld_library_path("/opt/a;/opt/b:c;", ';');
+    let paths: Vec<_> = dirs.iter().map(|d| d.path.clone()).collect();
+    assert_eq!(
+        paths,
+        vec![
+            PathBuf::from("/opt/a"),
+            PathBuf::from("/opt/b:c"),
+            PathBuf::from(".")
+        ]
+    );
+    assert!(dirs.iter().all(|d| d.source == SearchSource::LdLibraryPath));
+    assert_eq!(RlddOptions::default().ld_path_separator, ':');
+}
+
+#[test]
+fn test_permission_denied() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let tree = TempTree::new("perm");
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("libperm_locked.so")
+            .rpath("$ORIGIN/../lib"),
+    );
+    let lib = tree.write_elf("lib/libperm_locked.so", &ElfFixture::lib());
+    fs::set_permissions(&lib, fs::Permissions::from_mode(0o000)).unwrap();
+    if File::open(&lib).is_ok() {
+        // running as root, permissions are not enforced
+        return;
+    }
+
+    let info = rldd_rex(&bin).unwrap();
+    assert_eq!(
+        info.deps,
+        vec![("libperm_locked.so".to_string(), DepStatus::PermissionDenied)]
+    );
+    assert!(info.used_dirs().is_empty());
+}
+
+#[test]
+fn test_summary_line() {
+    let tree = TempTree::new("summary");
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("libsummary_ok.so")
+            .needed("libsummary_missing.so")
+            .rpath("$ORIGIN/../lib"),
+    );
+    tree.write_elf("lib/libsummary_ok.so", &ElfFixture::lib());
+
+    let info = rldd_rex(&bin).unwrap();
+    let line = to_summary_line(&bin, &info);
+    let fields: Vec<_> = line.split('\t').collect();
+    assert_eq!(
+        fields,
+        vec![bin.to_str().unwrap(), "Elf64", "Dynamic", "1/2", "1"]
+    );
+
+    // a library for the wrong class counts as missing too
+    let host_machine = ElfFixture::lib().machine;
+    tree.write_elf(
+        "lib/libsummary_32.so",
+        &ElfFixture::lib().elf32().machine(host_machine),
+    );
+    let mismatched = tree.write_elf(
+        "bin/mismatched",
+        &ElfFixture::exe()
+            .needed("libsummary_ok.so")
+            .needed("libsummary_32.so")
+            .rpath("$ORIGIN/../lib"),
+    );
+    let info = rldd_rex(&mismatched).unwrap();
+    assert_eq!(info.deps[1].1, DepStatus::ArchMismatch);
+    let line = to_summary_line(&mismatched, &info);
+    assert!(line.ends_with("\t1/2\t1"), "{}", line);
+}
+
+#[test]
+fn test_origin_of_requesting_object() {
+    let tree = TempTree::new("origin");
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("liborigin_mid.so")
+            .rpath("$ORIGIN/../opt/mid"),
+    );
+    tree.write_elf(
+        "opt/mid/liborigin_mid.so",
+        &ElfFixture::lib()
+            .needed("liborigin_plugin.so")
+            .needed("liborigin_braced.so")
+            .runpath("$ORIGIN/plugins")
+            .runpath("${ORIGIN}/braced"),
+    );
+    let plugin = tree.write_elf("opt/mid/plugins/liborigin_plugin.so", &ElfFixture::lib());
+    let braced = tree.write_elf("opt/mid/braced/liborigin_braced.so", &ElfFixture::lib());
+
+    let info = rldd_rex(&bin).unwrap();
+    let status = |name: &str| {
+        info.deps
+            .iter()
+            .find(|(n, _)| n == name)
+            .and_then(|(_, s)| match s {
+                DepStatus::Found(path) => fs::canonicalize(path).ok(),
+                _ => None,
+            })
+    };
+    assert_eq!(status("liborigin_plugin.so"), Some(plugin));
+    assert_eq!(status("liborigin_braced.so"), Some(braced));
+}
+
+#[test]
+fn test_load_order() {
+    let tree = TempTree::new("order");
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("liborder_a.so")
+            .needed("liborder_b.so")
+            .rpath("$ORIGIN/../lib"),
+    );
+    let lib = ElfFixture::lib().rpath("$ORIGIN");
+    tree.write_elf("lib/liborder_a.so", &lib.clone().needed("liborder_c.so"));
+    tree.write_elf("lib/liborder_b.so", &lib.clone().needed("liborder_d.so"));
+    tree.write_elf("lib/liborder_c.so", &lib);
+    tree.write_elf("lib/liborder_d.so", &lib);
+    let names = |info: &RlddRexInfo| {
+        info.deps
+            .iter()
+            .map(|(n, _)| n.trim_start_matches("liborder_").to_string())
+            .collect::<Vec<_>>()
+    };
+
+    let info = rldd_rex(&bin).unwrap();
+    assert_eq!(names(&info), vec!["c.so", "a.so", "d.so", "b.so"]);
+
+    let options = RlddOptions {
+        order: DepOrder::LoadOrder,
+        ..Default::default()
+    };
+    let info = rldd_rex_with_options(&bin, options).unwrap();
+    assert_eq!(names(&info), vec!["a.so", "b.so", "c.so", "d.so"]);
+    assert!(
+        info.libs
+            .iter()
+            .zip(&info.deps)
+            .all(|(l, (n, _))| &l.name == n)
+    );
+}
+
+#[test]
+fn test_exec_stack_closure() {
+    let tree = TempTree::new("execstack");
+    let exe = ElfFixture::exe().rpath("$ORIGIN/../lib");
+    let clean = tree.write_elf("bin/clean", &exe.clone().needed("libstack_ok.so"));
+    let bad = tree.write_elf("bin/bad", &exe.needed("libstack_x.so"));
+    tree.write_elf("lib/libstack_ok.so", &ElfFixture::lib());
+    tree.write_elf("lib/libstack_x.so", &ElfFixture::lib().exec_stack());
+
+    let info = rldd_rex(&bad).unwrap();
+    assert!(!info.executable_stack);
+    assert!(info.libs[0].executable_stack);
+    assert!(closure_has_exec_stack(&bad));
+    assert!(!closure_has_exec_stack(&clean));
+
+    let mut no_stack = ElfFixture::exe();
+    no_stack.phdrs.clear();
+    let legacy = tree.write_elf("bin/legacy", &no_stack);
+    assert!(rldd_rex(&legacy).unwrap().executable_stack);
+}
+
+#[test]
+fn test_canonicalize_root() {
+    let tree = TempTree::new("canon");
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("libcanon_priv.so")
+            .rpath("$ORIGIN/../private"),
+    );
+    tree.write_elf("private/libcanon_priv.so", &ElfFixture::lib());
+    let link = tree.mkdir("links/deep").join("app");
+    std::os::unix::fs::symlink(&bin, &link).unwrap();
+
+    let real = rldd_rex(&bin).unwrap();
+    let via_link = rldd_rex(&link).unwrap();
+    assert!(real.deps[0].1.is_resolved());
+    assert_eq!(real.deps, via_link.deps);
+    assert_eq!(real.search_dirs, via_link.search_dirs);
+
+    let options = RlddOptions {
+        canonicalize_root: false,
+        ..Default::default()
+    };
+    let raw = rldd_rex_with_options(&link, options).unwrap();
+    assert_eq!(raw.deps[0].1, DepStatus::NotFound);
+}
+
+#[test]
+fn test_same_closure() {
+    let tree = TempTree::new("closure");
+    let exe = ElfFixture::exe().rpath("$ORIGIN/../lib");
+    let one = tree.write_elf(
+        "bin/one",
+        &exe.clone()
+            .needed("libclosure_a.so")
+            .needed("libclosure_b.so"),
+    );
+    let two = tree.write_elf(
+        "bin/two",
+        &exe.clone()
+            .needed("libclosure_b.so")
+            .needed("libclosure_a.so"),
+    );
+    let three = tree.write_elf("bin/three", &exe.needed("libclosure_a.so"));
+    tree.write_elf("lib/libclosure_a.so", &ElfFixture::lib());
+    tree.write_elf("lib/libclosure_b.so", &ElfFixture::lib());
+
+    assert!(same_closure(&one, &two));
+    assert!(!same_closure(&one, &three));
+}
+
+#[test]
+fn test_dir_contents_map() {
+    let tree = TempTree::new("offline");
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("liboffline_remote.so")
+            .needed("liboffline_absent.so")
+            .rpath("/nonexistent/remote/lib"),
+    );
+
+    let mut contents = HashMap::new();
+    contents.insert(
+        PathBuf::from("/nonexistent/remote/lib"),
+        vec!["liboffline_remote.so".to_string()],
+    );
+    let options = RlddOptions {
+        dir_contents: Some(contents),
+        ..Default::default()
+    };
+    let info = rldd_rex_with_options(&bin, options).unwrap();
+    assert_eq!(
+        info.deps,
+        vec![
+            (
+                "liboffline_remote.so".to_string(),
+                DepStatus::Found("/nonexistent/remote/lib/liboffline_remote.so".into())
+            ),
+            ("liboffline_absent.so".to_string(), DepStatus::NotFound),
+        ]
+    );
+    assert_eq!(info.libs[0].source, Some(SearchSource::Rpath));
+}
+
+#[test]
+fn test_usrmerge_search_order() {
+    let tree = TempTree::new("usrmerge");
+    tree.mkdir("usr/lib");
+    tree.mkdir("usr/local/lib");
+    std::os::unix::fs::symlink("usr/lib", tree.join("lib")).unwrap();
+    assert!(is_usrmerge(tree.path()));
+
+    let dirs = vec![
+        SearchDir {
+            path: tree.join("lib"),
+            source: SearchSource::System,
+        },
+        SearchDir {
+            path: tree.join("usr/local/lib"),
+            source: SearchSource::LdSoConf,
+        },
+        SearchDir {
+            path: tree.join("usr/lib"),
+            source: SearchSource::LdSoConf,
+        },
+    ];
+    let uniq = dedup_search_dirs(dirs);
+    assert_eq!(
+        uniq,
+        vec![
+            SearchDir {
+                path: tree.join("usr/lib"),
+                source: SearchSource::System,
+            },
+            SearchDir {
+                path: tree.join("usr/local/lib"),
+                source: SearchSource::LdSoConf,
+            },
+        ]
+    );
+
+    let split = TempTree::new("usrsplit");
+    split.mkdir("lib");
+    split.mkdir("usr/lib");
+    assert!(!is_usrmerge(split.path()));
+}
+
+#[test]
+fn test_conflict_severity() {
+    assert_eq!(soname_base("libfoo.so.1.2"), "libfoo");
+    assert_eq!(so_version("libfoo.so.1.2.3"), Some(vec![1, 2, 3]));
+    assert_eq!(so_version("libfoo.so"), None);
+
+    let tree = TempTree::new("conflict");
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("libconf_maj.so.1")
+            .needed("libconf_maj.so.2")
+            .needed("libconf_pat.so.1")
+            .needed("libconf_mid.so")
+            .rpath("$ORIGIN/../opt/a"),
+    );
+    tree.write_elf("opt/a/libconf_maj.so.1", &ElfFixture::lib());
+    tree.write_elf("opt/a/libconf_maj.so.2", &ElfFixture::lib());
+    tree.write_elf(
+        "opt/a/libconf_mid.so",
+        &ElfFixture::lib()
+            .needed("libconf_pat.so.1")
+            .rpath("$ORIGIN/../b"),
+    );
+    tree.write_elf("opt/a/libconf_pat.so.1.0.0", &ElfFixture::lib());
+    tree.write_elf("opt/b/libconf_pat.so.1.0.5", &ElfFixture::lib());
+    std::os::unix::fs::symlink("libconf_pat.so.1.0.0", tree.join("opt/a/libconf_pat.so.1"))
+        .unwrap();
+    std::os::unix::fs::symlink("libconf_pat.so.1.0.5", tree.join("opt/b/libconf_pat.so.1"))
+        .unwrap();
+
+    let info = rldd_rex(&bin).unwrap();
+    let severity = |base: &str| {
+        info.conflicts
+            .iter()
+            .find(|c| c.base == base)
+            .map(|c| c.severity)
+    };
+    assert_eq!(info.conflicts.len(), 2);
+    assert_eq!(severity("libconf_maj"), Some(ConflictSeverity::Major));
+    assert_eq!(severity("libconf_pat"), Some(ConflictSeverity::Patch));
+    assert_eq!(severity("libconf_mid"), None);
+}
+
+#[test]
+fn test_table() {
+    let tree = TempTree::new("table");
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("libtable_a.so")
+            .needed("libtable_missing.so")
+            .rpath(tree.join("opt,x").to_str().unwrap()),
+    );
+    let lib = tree.write_elf("opt,x/libtable_a.so", &ElfFixture::lib());
+
+    let table = to_table(&rldd_rex(&bin).unwrap());
+    let rows: Vec<_> = table.lines().collect();
+    assert_eq!(rows[0], "index,soname,status,path,source,depth");
+    assert_eq!(
+        rows[1],
+        format!("1,libtable_a.so,found,\"{}\",rpath,1", lib.display())
+    );
+    assert_eq!(rows[2], "2,libtable_missing.so,not found,,,1");
+    assert_eq!(rows.len(), 3);
+}
+
+#[test]
+fn test_stripped_section_headers() {
+    let tree = TempTree::new("sstrip");
+    tree.write_elf("lib/libsstrip_dep.so", &ElfFixture::lib());
+    let exe = ElfFixture::exe()
+        .needed("libsstrip_dep.so")
+        .rpath("$ORIGIN/../lib");
+
+    // no section header table at all
+    let stripped = tree.write_elf("bin/stripped", &exe);
+    // section header table pointing past the end of the file
+    let mut bytes = exe.bytes();
+    bytes[0x28..0x30].copy_from_slice(&0xffff_0000u64.to_le_bytes());
+    bytes[0x3a..0x3c].copy_from_slice(&64u16.to_le_bytes());
+    bytes[0x3c..0x3e].copy_from_slice(&4u16.to_le_bytes());
+    assert!(Elf::parse(&bytes).is_err());
+    let truncated = tree.write("bin/truncated", &bytes);
+
+    for bin in [stripped, truncated] {
+        let info = rldd_rex(&bin).unwrap();
+        assert_eq!(info.elf_type, ElfType::Dynamic);
+        assert_eq!(info.deps.len(), 1);
+        assert!(info.deps[0].1.is_resolved(), "{:?}", info.deps);
+    }
+}
+
+#[test]
+fn test_conflict_winner() {
+    let tree = TempTree::new("winner");
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("libwin_mid.so")
+            .needed("libwin_dup.so.1")
+            .needed("libwin_maj.so.2")
+            .needed("libwin_maj.so.1")
+            .rpath("$ORIGIN/../opt/a"),
+    );
+    tree.write_elf(
+        "opt/a/libwin_mid.so",
+        &ElfFixture::lib()
+            .needed("libwin_dup.so.1")
+            .rpath("$ORIGIN/../b"),
+    );
+    let root_dup = tree.write_elf("opt/a/libwin_dup.so.1", &ElfFixture::lib());
+    tree.write_elf("opt/b/libwin_dup.so.1", &ElfFixture::lib());
+    tree.write_elf("opt/a/libwin_maj.so.1", &ElfFixture::lib());
+    let maj2 = tree.write_elf("opt/a/libwin_maj.so.2", &ElfFixture::lib());
+
+    let info = rldd_rex(&bin).unwrap();
+    let winner = |base: &str| {
+        let c = info.conflicts.iter().find(|c| c.base == base).unwrap();
+        fs::canonicalize(&c.winner).unwrap()
+    };
+    // the depth first walk meets libwin_dup.so.1 through libwin_mid.so first,
+    // but ld.so loads the copy the executable itself finds
+    let walked = info.libs.iter().find(|l| l.name == "libwin_dup.so.1");
+    assert!(
+        walked
+            .unwrap()
+            .path
+            .as_ref()
+            .unwrap()
+            .ends_with("b/libwin_dup.so.1")
+    );
+    assert_eq!(winner("libwin_dup"), root_dup);
+    assert_eq!(winner("libwin_maj"), maj2);
+}
+
+#[test]
+fn test_assume_present() {
+    let tree = TempTree::new("assume");
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("libassume_core.so.1")
+            .needed("libassume_vendor.so"),
+    );
+
+    let options = RlddOptions {
+        assume_present: HashSet::from(["libassume_core.so.1".to_string()]),
+        ..Default::default()
+    };
+    let info = rldd_rex_with_options(&bin, options).unwrap();
+    assert_eq!(
+        info.deps,
+        vec![
+            ("libassume_core.so.1".to_string(), DepStatus::Assumed),
+            ("libassume_vendor.so".to_string(), DepStatus::NotFound),
+        ]
+    );
+    assert!(info.deps[0].1.is_resolved());
+    assert!(to_table(&info).contains("1,libassume_core.so.1,assumed,,,1"));
+}
+
+#[test]
+fn test_missing_from_base() {
+    let tree = TempTree::new("base");
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("libbase_shared.so")
+            .needed("libbase_extra.so")
+            .rpath("$ORIGIN/../lib"),
+    );
+    let shared = tree.write_elf("lib/libbase_shared.so", &ElfFixture::lib());
+    let extra = tree.write_elf("lib/libbase_extra.so", &ElfFixture::lib());
+
+    let base = HashSet::from([shared]);
+    let missing = missing_from_base(&bin, &base);
+    assert_eq!(missing.len(), 1);
+    assert_eq!(missing[0].name, "libbase_extra.so");
+    assert_eq!(
+        fs::canonicalize(missing[0].path.as_ref().unwrap()).unwrap(),
+        extra
+    );
+}
+
+#[test]
+fn test_inconsistent_header() {
+    let tree = TempTree::new("header");
+    let is_inconsistent =
+        |path: &Path| matches!(rldd_rex(path), Err(RlddError::InconsistentHeader(_)));
+
+    let i386_64 = tree.write_elf("bin/i386-64", &ElfFixture::exe().machine(EM_386));
+    assert!(is_inconsistent(&i386_64));
+
+    let mut be = ElfFixture::exe();
+    be.big_endian = true;
+    let x86_be = tree.write_elf("bin/x86-be", &be);
+    assert!(is_inconsistent(&x86_be));
+
+    let x32 = tree.write_elf("bin/x32", &ElfFixture::exe().elf32().machine(EM_X86_64));
+    assert_eq!(rldd_rex(&x32).unwrap().arch, ElfArch::Elf32);
+}
+
+#[test]
+fn test_force_libc() {
+    let bytes = ElfFixture::exe().bytes();
+    let elf = Elf::parse(&bytes).unwrap();
+    let has_gnu_dirs = |force: Option<LibcFamily>| {
+        let options = RlddOptions {
+            force_libc: force,
+            ..Default::default()
+        };
+        build_search_dirs(
+            elf.interpreter,
+            AbiFlags::of(&elf, &bytes),
+            ElfArch::Elf64,
+            ElfMachine::X86_64,
+            &options,
+        )
+        .iter()
+        .any(|d| d.path.to_string_lossy().contains("x86_64-linux-gnu"))
+    };
+
+    assert!(has_gnu_dirs(None));
+    assert!(has_gnu_dirs(Some(LibcFamily::Glibc)));
+    assert!(!has_gnu_dirs(Some(LibcFamily::Musl)));
+    assert_eq!(
+        musl_path_files(ElfArch::Elf64, ElfMachine::Arm64, AbiFlags::default()),
+        vec![PathBuf::from("/etc/ld-musl-aarch64.path")]
+    );
+}
+
+#[test]
+fn test_raw_interpreter() {
+    let tree = TempTree::new("interp");
+    let mut plain = ElfFixture::exe();
+    plain.interp = Some("/lib64/ld-linux-x86-64.so.2".into());
+    let plain = tree.write_elf("bin/plain", &plain);
+    assert_eq!(
+        raw_interpreter(&plain).as_deref(),
+        Some("/lib64/ld-linux-x86-64.so.2")
+    );
+
+    let mut padded = ElfFixture::exe();
+    padded.interp = Some("/opt/ld.so\0\0\0".into());
+    let padded = tree.write_elf("bin/padded", &padded);
+    assert_eq!(
+        raw_interpreter(&padded).as_deref(),
+        Some("/opt/ld.so\0\0\0")
+    );
+
+    let lib = tree.write_elf("lib/libnointerp.so", &ElfFixture::lib());
+    assert_eq!(raw_interpreter(&lib), None);
+}
+
+#[test]
+fn test_dir_listing_invalidation() {
+    let tree = TempTree::new("negcache");
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("libnegcache_late.so")
+            .rpath("$ORIGIN/../plugins"),
+    );
+    let dir = tree.mkdir("plugins");
+
+    let resolver = Resolver::with_options(RlddOptions {
+        cache_dir_listings: true,
+        ..Default::default()
+    });
+    assert_eq!(
+        resolver.rldd_rex(&bin).unwrap().deps[0].1,
+        DepStatus::NotFound
+    );
+    assert_eq!(
+        resolver.rldd_rex(&bin).unwrap().deps[0].1,
+        DepStatus::NotFound
+    );
+
+    tree.write_elf("plugins/libnegcache_late.so", &ElfFixture::lib());
+    // coarse timestamps could leave the dir mtime unchanged within one tick
+    let later = std::time::SystemTime::now() + std::time::Duration::from_secs(5);
+    File::open(&dir).unwrap().set_modified(later).unwrap();
+
+    let status = &resolver.rldd_rex(&bin).unwrap().deps[0].1;
+    assert!(status.is_resolved(), "{}", status);
+}
+
+#[test]
+fn test_interpreter_status() {
+    let tree = TempTree::new("interp-status");
+    let loader = tree.write_elf("lib/ld-musl-x86_64.so.1", &ElfFixture::lib());
+    let mut present = ElfFixture::exe();
+    present.interp = Some(loader.display().to_string());
+    let present = tree.write_elf("bin/present", &present);
+
+    let mut absent = ElfFixture::exe();
+    absent.interp = Some("/nonexistent/ld-linux-x86-64.so.2".into());
+    let absent = tree.write_elf("bin/absent", &absent);
+
+    assert_eq!(
+        interpreter_status(&present),
+        InterpreterStatus {
+            path: Some(loader),
+            exists: true,
+            family: Some(LibcFamily::Musl),
+        }
+    );
+    assert_eq!(
+        interpreter_status(&absent),
+        InterpreterStatus {
+            path: Some(PathBuf::from("/nonexistent/ld-linux-x86-64.so.2")),
+            exists: false,
+            family: Some(LibcFamily::Glibc),
+        }
+    );
+    let lib = tree.join("lib/ld-musl-x86_64.so.1");
+    assert_eq!(interpreter_status(&lib).path, None);
+}
+
+#[test]
+fn test_static_tls() {
+    let tree = TempTree::new("tls");
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("libtls_static.so")
+            .needed("libtls_dynamic.so")
+            .rpath("$ORIGIN/../lib"),
+    );
+    tree.write_elf(
+        "lib/libtls_static.so",
+        &ElfFixture::lib().dyn_entry(goblin::elf::dynamic::DT_FLAGS, DF_STATIC_TLS),
+    );
+    tree.write_elf("lib/libtls_dynamic.so", &ElfFixture::lib());
+
+    let info = rldd_rex(&bin).unwrap();
+    let flags: Vec<_> = info
+        .libs
+        .iter()
+        .map(|l| (l.name.as_str(), l.uses_static_tls))
+        .collect();
+    assert_eq!(
+        flags,
+        vec![("libtls_static.so", true), ("libtls_dynamic.so", false)]
+    );
+}
+
+#[test]
+fn test_resolve_many() {
+    let mut contents = HashMap::new();
+    contents.insert(
+        PathBuf::from("/usr/lib"),
+        vec!["libmanifest_present.so".to_string()],
+    );
+    let options = RlddOptions {
+        dir_contents: Some(contents),
+        ..Default::default()
+    };
+    let names = [
+        "libmanifest_present.so".to_string(),
+        "libmanifest_missing.so".to_string(),
+    ];
+    let statuses = resolve_many(&names, ElfArch::Elf64, ElfMachine::X86_64, options);
+    assert_eq!(
+        statuses,
+        vec![
+            (
+                "libmanifest_present.so".to_string(),
+                DepStatus::Found("/usr/lib/libmanifest_present.so".into())
+            ),
+            ("libmanifest_missing.so".to_string(), DepStatus::NotFound),
+        ]
+    );
+
+    // extra_dirs count the same as in rldd_rex
+    let tree = TempTree::new("resolve-many-extra");
+    let vendor = tree.mkdir("vendor");
+    let lib = tree.write_elf("vendor/libmanifest_extra.so", &ElfFixture::lib());
+    let options = RlddOptions {
+        extra_dirs: vec![vendor],
+        ..Default::default()
+    };
+    let names = ["libmanifest_extra.so".to_string()];
+    let statuses = resolve_many(&names, ElfArch::Elf64, ElfMachine::X86_64, options);
+    assert_eq!(statuses, vec![(names[0].clone(), DepStatus::Found(lib))]);
+}
+
+#[test]
+fn test_relro() {
+    use goblin::elf::dynamic::{DF_1_NOW, DT_FLAGS, DT_FLAGS_1};
+    use goblin::elf::program_header::PF_R;
+
+    let tree = TempTree::new("relro");
+    let relro_of = |name: &str, fixture: ElfFixture| {
+        let info = rldd_rex(tree.write_elf(name, &fixture)).unwrap();
+        (info.relro, info.relro_size)
+    };
+
+    assert_eq!(relro_of("bin/none", ElfFixture::exe()), (Relro::None, 0));
+    assert_eq!(
+        relro_of(
+            "bin/partial",
+            ElfFixture::exe().phdr(PT_GNU_RELRO, PF_R, 0x1000)
+        ),
+        (Relro::Partial, 0x1000)
+    );
+    assert_eq!(
+        relro_of(
+            "bin/full",
+            ElfFixture::exe()
+                .phdr(PT_GNU_RELRO, PF_R, 0x2000)
+                .dyn_entry(DT_BIND_NOW, 0)
+        ),
+        (Relro::Full, 0x2000)
+    );
+    assert_eq!(
+        relro_of(
+            "bin/full_flags",
+            ElfFixture::exe()
+                .phdr(PT_GNU_RELRO, PF_R, 0x2000)
+                .dyn_entry(DT_FLAGS, DF_BIND_NOW)
+        ),
+        (Relro::Full, 0x2000)
+    );
+    assert_eq!(
+        relro_of(
+            "bin/full_flags_1",
+            ElfFixture::exe()
+                .phdr(PT_GNU_RELRO, PF_R, 0x2000)
+                .dyn_entry(DT_FLAGS_1, DF_1_NOW)
+        ),
+        (Relro::Full, 0x2000)
+    );
+}
+
+#[test]
+fn test_dir_listing_prefilter() {
+    let tree = TempTree::new("bigdir");
+    let dir = tree.mkdir("lib");
+    for i in 0..2000 {
+        File::create(dir.join(format!("libbulk{}.so", i))).unwrap();
+    }
+    tree.write_elf("lib/libbulk_target.so", &ElfFixture::lib());
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("libbulk_target.so")
+            .needed("libbulk_missing.so")
+            .needed("zlib_missing.so")
+            .rpath("$ORIGIN/../lib"),
+    );
+
+    let resolver = Resolver::with_options(RlddOptions {
+        cache_dir_listings: true,
+        ..Default::default()
+    });
+    let info = resolver.rldd_rex(&bin).unwrap();
+    assert!(info.deps[0].1.is_resolved());
+    assert_eq!(info.deps[1].1, DepStatus::NotFound);
+    assert_eq!(info.deps[2].1, DepStatus::NotFound);
+    // only the present library is ever stat'ed
+    assert_eq!(resolver.probes.load(Ordering::Relaxed), 1);
+
+    let listings = resolver.listings.lock().unwrap();
+    let listing = &listings[&dir];
+    assert!(listing.contains("libbulk_target.so"));
+    assert!(!listing.contains("libbulk_missing.so"));
+    assert!(!listing.contains("zlib_missing.so"));
+    assert!(!listing.contains(""));
+}
+
+#[test]
+fn test_shared_dedup_per_arch() {
+    let tree = TempTree::new("dedup-arch");
+    let bin64 = tree.write_elf(
+        "bin/app64",
+        &ElfFixture::exe()
+            .needed("libarch_c.so.6")
+            .rpath("$ORIGIN/../amd64"),
+    );
+    let bin32 = tree.write_elf(
+        "bin/app32",
+        &ElfFixture::exe()
+            .elf32()
+            .needed("libarch_c.so.6")
+            .rpath("$ORIGIN/../i386"),
+    );
+    let lib64 = tree.write_elf("amd64/libarch_c.so.6", &ElfFixture::lib());
+    let lib32 = tree.write_elf("i386/libarch_c.so.6", &ElfFixture::lib().elf32());
+
+    let resolver = Resolver::with_options(RlddOptions {
+        dedup_scope: DedupScope::Shared,
+        ..Default::default()
+    });
+    let resolved = |info: RlddRexInfo| {
+        let lib = info.libs.iter().find(|l| l.name == "libarch_c.so.6")?;
+        fs::canonicalize(lib.path.as_ref()?).ok()
+    };
+    let lib64 = fs::canonicalize(lib64).unwrap();
+    let lib32 = fs::canonicalize(lib32).unwrap();
+
+    assert_eq!(resolved(resolver.rldd_rex(&bin64).unwrap()), Some(lib64));
+    assert_eq!(resolved(resolver.rldd_rex(&bin32).unwrap()), Some(lib32));
+    // same arch still dedups
+    assert_eq!(resolved(resolver.rldd_rex(&bin64).unwrap()), None);
+}
+
+#[test]
+fn test_weight_report() {
+    let tree = TempTree::new("weight");
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("libweight_heavy.so")
+            .needed("libweight_light.so")
+            .rpath("$ORIGIN/../lib"),
+    );
+    let heavy = [
+        tree.write_elf(
+            "lib/libweight_heavy.so",
+            &ElfFixture::lib()
+                .needed("libweight_a.so")
+                .needed("libweight_b.so")
+                .needed("libweight_common.so"),
+        ),
+        tree.write_elf("lib/libweight_a.so", &ElfFixture::lib()),
+        tree.write_elf("lib/libweight_b.so", &ElfFixture::lib()),
+    ];
+    let light = tree.write_elf(
+        "lib/libweight_light.so",
+        &ElfFixture::lib().needed("libweight_common.so"),
+    );
+    let common = tree.write_elf("lib/libweight_common.so", &ElfFixture::lib());
+    let size = |p: &PathBuf| fs::metadata(p).unwrap().len();
+
+    assert_eq!(
+        weight_report(&bin),
+        vec![
+            (
+                "libweight_heavy.so".to_string(),
+                WeightStats {
+                    libs: 3,
+                    bytes: heavy.iter().map(size).sum(),
+                }
+            ),
+            (
+                "libweight_light.so".to_string(),
+                WeightStats {
+                    libs: 1,
+                    bytes: size(&light),
+                }
+            ),
+            (
+                SHARED_BUCKET.to_string(),
+                WeightStats {
+                    libs: 1,
+                    bytes: size(&common),
+                }
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_packed_binary() {
+    let tree = TempTree::new("packed");
+    let mut stub = ElfFixture::exe();
+    stub.interp = None;
+    stub.dynamic = false;
+    let mut bytes = stub.bytes();
+    bytes.extend_from_slice(b"\0\0UPX!\x0d\x16\x08\x07");
+    let packed = tree.write("bin/packed", &bytes);
+    let mut plain = ElfFixture::exe();
+    plain.interp = Some("/lib64/ld-linux-x86-64.so.2".into());
+    let plain = tree.write_elf("bin/plain", &plain);
+
+    let info = rldd_rex(&packed).unwrap();
+    assert_eq!(info.packed, Some(PackerKind::Upx));
+    assert!(info.warnings.iter().any(|w| w.contains("packed")));
+    assert_eq!(rldd_rex(&plain).unwrap().packed, None);
+}
+
+#[test]
+fn test_record_attempts() {
+    let tree = TempTree::new("attempts");
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("libattempts_missing.so")
+            .rpath("/nonexistent/attempts"),
+    );
+
+    let info = rldd_rex(&bin).unwrap();
+    assert!(info.libs[0].attempted.is_empty());
+
+    let options = RlddOptions {
+        record_attempts: true,
+        ..Default::default()
+    };
+    let info = rldd_rex_with_options(&bin, options).unwrap();
+    assert_eq!(info.deps[0].1, DepStatus::NotFound);
+    let (ld_path, rest): (Vec<_>, Vec<_>) = info
+        .search_dirs
+        .iter()
+        .partition(|d| d.source == SearchSource::LdLibraryPath);
+    let expected: Vec<_> = [PathBuf::from("/nonexistent/attempts")]
+        .into_iter()
+        .chain(ld_path.into_iter().chain(rest).map(|d| d.path.clone()))
+        .map(|dir| dir.join("libattempts_missing.so"))
+        .collect();
+    assert_eq!(info.libs[0].attempted, expected);
+}
+
+#[test]
+fn test_lib_token_per_arch() {
+    let bin = Path::new("/opt/app/bin/app");
+    let cases = [
+        (ElfArch::Elf64, ElfMachine::X86_64, "/opt/app/bin/../lib64"),
+        (ElfArch::Elf32, ElfMachine::X86_64, "/opt/app/bin/../libx32"),
+        (ElfArch::Elf32, ElfMachine::X86, "/opt/app/bin/../lib"),
+        (ElfArch::Elf64, ElfMachine::Arm64, "/opt/app/bin/../lib"),
+        (ElfArch::Elf32, ElfMachine::Arm32, "/opt/app/bin/../lib"),
+        (ElfArch::Elf64, ElfMachine::PowerPC, "/opt/app/bin/../lib64"),
+        (ElfArch::Elf64, ElfMachine::RiscV64, "/opt/app/bin/../lib64"),
+    ];
+    for (arch, machine, expected) in cases {
+        assert_eq!(
+            resolve_origin(bin, "$ORIGIN/../$LIB", arch, machine),
+            PathBuf::from(expected),
+            "{:?} {:?}",
+            arch,
+            machine
+        );
+    }
+
+    let x86_64 = (ElfArch::Elf64, ElfMachine::X86_64);
+    assert_eq!(
+        resolve_origin(bin, "/usr/${LIB}/plugins", x86_64.0, x86_64.1),
+        PathBuf::from("/usr/lib64/plugins")
+    );
+    assert_eq!(
+        resolve_origin(bin, "/opt/$LIBRARY", x86_64.0, x86_64.1),
+        PathBuf::from("/opt/$LIBRARY")
+    );
+}
+
+#[test]
+fn test_platform_token() {
+    let bin = Path::new("/opt/app/bin/app");
+    assert_eq!(
+        resolve_origin(
+            bin,
+            "${ORIGIN}/../$LIB/$PLATFORM",
+            ElfArch::Elf64,
+            ElfMachine::X86_64
+        ),
+        PathBuf::from("/opt/app/bin/../lib64/x86_64")
+    );
+    assert_eq!(
+        resolve_origin(
+            bin,
+            "$ORIGIN/../$LIB/${PLATFORM}",
+            ElfArch::Elf32,
+            ElfMachine::X86
+        ),
+        PathBuf::from("/opt/app/bin/../lib/i686")
+    );
+    // no fixed AT_PLATFORM, stays literal
+    assert_eq!(
+        resolve_origin(bin, "/opt/$PLATFORM", ElfArch::Elf64, ElfMachine::PowerPC),
+        PathBuf::from("/opt/$PLATFORM")
+    );
+}
+
+#[test]
+fn test_assert_deps_resolved() {
+    let tree = TempTree::new("assert-deps");
+    let ok = tree.write_elf(
+        "bin/ok",
+        &ElfFixture::exe()
+            .needed("libassert_present.so")
+            .rpath("$ORIGIN/../lib"),
+    );
+    let broken = tree.write_elf(
+        "bin/broken",
+        &ElfFixture::exe()
+            .needed("libassert_present.so")
+            .needed("libassert_missing.so")
+            .rpath("$ORIGIN/../lib"),
+    );
+    tree.write_elf("lib/libassert_present.so", &ElfFixture::lib());
+
+    assert_deps_resolved(&ok).unwrap();
+    assert_eq!(
+        assert_deps_resolved(&broken),
+        Err(vec!["libassert_missing.so".to_string()])
+    );
+}
+
+#[test]
+fn test_search_libexec() {
+    let has_libexec = |search_libexec: bool| {
+        let options = RlddOptions {
+            search_libexec,
+            ..Default::default()
+        };
+        build_search_dirs(
+            None,
+            AbiFlags::default(),
+            ElfArch::Elf64,
+            ElfMachine::X86_64,
+            &options,
+        )
+        .iter()
+        .any(|d| d.path.ends_with("libexec"))
+    };
+    assert!(!has_libexec(false));
+    assert!(has_libexec(true) || !Path::new("/usr/libexec").exists());
+}
+
+#[test]
+fn test_secure_exec_ignores_untrusted_rpath() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let tree = TempTree::new("setuid");
+    let exe = ElfFixture::exe()
+        .needed("libsetuid_dep.so")
+        .rpath("$ORIGIN/../lib");
+    let plain = tree.write_elf("bin/plain", &exe);
+    let suid = tree.write_elf("bin/suid", &exe);
+    fs::set_permissions(&suid, fs::Permissions::from_mode(0o4755)).unwrap();
+    tree.write_elf("lib/libsetuid_dep.so", &ElfFixture::lib());
+
+    let info = rldd_rex(&plain).unwrap();
+    assert!(!info.secure_exec);
+    assert!(info.deps[0].1.is_resolved());
+    assert!(info.ignored_dirs.is_empty());
+
+    let info = rldd_rex(&suid).unwrap();
+    assert!(info.secure_exec);
+    assert_eq!(info.deps[0].1, DepStatus::NotFound);
+    assert!(info.ignored_dirs.contains(&SearchDir {
+        path: tree.join("bin/../lib"),
+        source: SearchSource::Rpath,
+    }));
+    assert!(
+        info.search_dirs
+            .iter()
+            .all(|d| matches!(d.source, SearchSource::System | SearchSource::LdSoConf))
+    );
+}
+
+#[test]
+fn test_virtual_lib() {
+    let tree = TempTree::new("virtual");
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe().needed("libvirtual_vendored.so"),
+    );
+    assert_eq!(rldd_rex(&bin).unwrap().deps[0].1, DepStatus::NotFound);
+
+    let options = RlddOptions::default().add_virtual_lib(
+        "libvirtual_vendored.so",
+        "/opt/vendor/libvirtual_vendored.so",
+    );
+    let info = rldd_rex_with_options(&bin, options).unwrap();
+    assert_eq!(
+        info.deps[0],
+        (
+            "libvirtual_vendored.so".to_string(),
+            DepStatus::Found("/opt/vendor/libvirtual_vendored.so".into())
+        )
+    );
+    assert_eq!(info.libs[0].source, Some(SearchSource::Virtual));
+}
+
+#[test]
+fn test_pie_classification() {
+    use goblin::elf::dynamic::DT_FLAGS_1;
+
+    let tree = TempTree::new("pie");
+    let mut pie = ElfFixture::lib();
+    pie.interp = Some("/lib64/ld-linux-x86-64.so.2".into());
+    let flagged = pie.clone().dyn_entry(DT_FLAGS_1, DF_1_PIE);
+    // runnable library like libc.so.6
+    let runnable_lib = pie.clone().soname("libpie_runnable.so.6");
+
+    let cases = [
+        ("bin/pie", pie, true),
+        ("bin/pie_flagged", flagged, true),
+        ("bin/non_pie", ElfFixture::exe(), false),
+        ("lib/libpie_plain.so", ElfFixture::lib(), false),
+        ("lib/libpie_runnable.so.6", runnable_lib, false),
+    ];
+    for (name, fixture, expected) in cases {
+        let path = tree.write_elf(name, &fixture);
+        assert_eq!(is_pie_executable(&path), expected, "{}", name);
+    }
+    // unreadable or missing files are just not PIE
+    assert!(!is_pie_executable(tree.join("bin/missing")));
+    assert!(!is_pie_executable(
+        tree.write("bin/script.sh", b"#!/bin/sh\n")
+    ));
+}
+
+#[test]
+fn test_riscv64_triplet_dirs() {
+    let tree = TempTree::new("riscv64");
+    let bin = tree.write_elf("bin/app", &ElfFixture::exe().machine(EM_RISCV));
+    let info = rldd_rex(&bin).unwrap();
+    assert_eq!(info.machine, ElfMachine::RiscV64);
+    let dirs: Vec<_> = info.search_dirs.iter().map(|d| d.path.clone()).collect();
+    assert!(
+        dirs.contains(&PathBuf::from("/usr/lib/riscv64-linux-gnu")),
+        "{:?}",
+        dirs
+    );
+}
+
+#[test]
+fn test_extra_ld_conf_files() {
+    let tree = TempTree::new("extra-conf");
+    let lib_dir = tree.mkdir("opt/lib");
+    let nested_dir = tree.mkdir("opt/nested");
+    tree.write(
+        "conf.d/nested.conf",
+        format!("{}\n", nested_dir.display()).as_bytes(),
+    );
+    let conf = tree.write(
+        "extra.conf",
+        format!(
+            "# captured from another host\n{}\ninclude {}/conf.d/*.conf\n",
+            lib_dir.display(),
+            tree.path().display()
+        )
+        .as_bytes(),
+    );
+    tree.write_elf("opt/lib/libextra_conf.so", &ElfFixture::lib());
+    let bin = tree.write_elf("bin/app", &ElfFixture::exe().needed("libextra_conf.so"));
+
+    let options = RlddOptions {
+        extra_ld_conf_files: vec![conf],
+        ..Default::default()
+    };
+    let info = rldd_rex_with_options(&bin, options).unwrap();
+    for dir in [&lib_dir, &nested_dir] {
+        assert!(info.search_dirs.contains(&SearchDir {
+            path: fs::canonicalize(dir).unwrap(),
+            source: SearchSource::LdSoConf,
+        }));
+    }
+    assert!(info.deps[0].1.is_resolved());
+    assert_eq!(info.libs[0].source, Some(SearchSource::LdSoConf));
+}
+
+#[test]
+fn test_cpp_runtime_conflict() {
+    let tree = TempTree::new("cpp-runtime");
+    let mixed = tree.write_elf(
+        "bin/mixed",
+        &ElfFixture::exe()
+            .needed("libstdc++.so.6")
+            .needed("libplugin_cpp.so")
+            .rpath("$ORIGIN/../lib"),
+    );
+    let single = tree.write_elf(
+        "bin/single",
+        &ElfFixture::exe()
+            .needed("libstdc++.so.6")
+            .rpath("$ORIGIN/../lib"),
+    );
+    tree.write_elf("lib/libstdc++.so.6", &ElfFixture::lib());
+    tree.write_elf(
+        "lib/libplugin_cpp.so",
+        &ElfFixture::lib().needed("libc++.so.1"),
+    );
+    tree.write_elf("lib/libc++.so.1", &ElfFixture::lib());
+
+    let info = rldd_rex(&mixed).unwrap();
+    assert!(info.cpp_runtime_conflict);
+    assert_eq!(
+        info.cpp_runtimes,
+        vec!["libstdc++.so.6".to_string(), "libc++.so.1".to_string()]
+    );
+
+    let info = rldd_rex(&single).unwrap();
+    assert!(!info.cpp_runtime_conflict);
+    assert_eq!(info.cpp_runtimes, vec!["libstdc++.so.6".to_string()]);
+}
+
+#[test]
+fn test_s390x_dirs() {
+    let machine = machine_from_e_machine(EM_S390, ElfArch::Elf64);
+    assert_eq!(machine, ElfMachine::S390x);
+
+    let dirs = default_dirs_for_arch_and_machine(ElfArch::Elf64, machine);
+    for expected in ["/lib/s390x-linux-gnu", "/usr/lib/s390x-linux-gnu"] {
+        assert!(dirs.contains(&PathBuf::from(expected)), "{:?}", dirs);
+    }
+    assert_eq!(
+        musl_path_files(ElfArch::Elf64, machine, AbiFlags::default()),
+        vec![PathBuf::from("/etc/ld-musl-s390x.path")]
+    );
+}
+
+#[test]
+fn test_loongarch64_dirs() {
+    assert_eq!(
+        machine_from_e_machine(EM_LOONGARCH, ElfArch::Elf64),
+        ElfMachine::LoongArch64
+    );
+    let dirs = default_dirs_for_arch_and_machine(ElfArch::Elf64, ElfMachine::LoongArch64);
+    for expected in [
+        "/lib/loongarch64-linux-gnu",
+        "/usr/lib/loongarch64-linux-gnu",
+    ] {
+        assert!(dirs.contains(&PathBuf::from(expected)), "{:?}", dirs);
+    }
+}
+
+#[test]
+fn test_cpu_baseline() {
+    let tree = TempTree::new("hwcaps");
+    let lib_dir = tree.mkdir("opt/lib");
+    let conf = tree.write("extra.conf", format!("{}\n", lib_dir.display()).as_bytes());
+    tree.write_elf("opt/lib/libhwcaps_v2.so", &ElfFixture::lib());
+    tree.write_elf(
+        "opt/lib/glibc-hwcaps/x86-64-v2/libhwcaps_v2.so",
+        &ElfFixture::lib(),
+    );
+    tree.write_elf("opt/lib/libhwcaps_v3.so", &ElfFixture::lib());
+    let v3 = tree.write_elf(
+        "opt/lib/glibc-hwcaps/x86-64-v3/libhwcaps_v3.so",
+        &ElfFixture::lib(),
+    );
+    tree.write_elf("opt/lib/libhwcaps_plain.so", &ElfFixture::lib());
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("libhwcaps_v2.so")
+            .needed("libhwcaps_v3.so")
+            .needed("libhwcaps_plain.so"),
+    );
+    let plain = tree.write_elf("bin/plain", &ElfFixture::exe().needed("libhwcaps_plain.so"));
+
+    let options = || RlddOptions {
+        extra_ld_conf_files: vec![conf.clone()],
+        ..Default::default()
+    };
+    let info = rldd_rex_with_options(&bin, options()).unwrap();
+    assert_eq!(info.machine, ElfMachine::X86_64);
+    assert_eq!(
+        info.deps[1].1,
+        DepStatus::Found(fs::canonicalize(v3).unwrap())
+    );
+    assert_eq!(info.cpu_baseline, Some("x86-64-v3".into()));
+    let plain = rldd_rex_with_options(&plain, options()).unwrap();
+    assert_eq!(plain.cpu_baseline, None);
+    assert_eq!(hwcaps_level("power10"), Some(10));
+    assert_eq!(hwcaps_level("z15"), Some(15));
+}
+
+#[test]
+fn test_libc_version() {
+    let tree = TempTree::new("libc-version");
+    let bin = tree.write_elf("bin/app", &ElfFixture::exe().needed("libc.so.6"));
+    let info = rldd_rex(&bin).unwrap();
+    let host_libc = info
+        .libs
+        .iter()
+        .find(|l| l.name == "libc.so.6")
+        .and_then(|l| l.path.clone());
+
+    match host_libc {
+        Some(path) if glibc_version(&path).is_some() => {
+            let version = info.libc_version.unwrap();
+            assert!(version.starts_with("2."), "{}", version);
+            assert!(so_version(&format!("x.so.{}", version)).is_some());
+        }
+        // no glibc on this host
+        _ => assert_eq!(info.libc_version, None),
+    }
+
+    let musl_like = tree.write_elf("lib/libc.so", &ElfFixture::lib());
+    assert_eq!(glibc_version(&musl_like), None);
+}
+
+#[test]
+fn test_musl_path_file_per_arch() {
+    let le = |e_flags| AbiFlags {
+        e_flags,
+        ..Default::default()
+    };
+    let be = |e_flags| AbiFlags {
+        e_flags,
+        big_endian: true,
+        ..Default::default()
+    };
+    let soft = |abi: AbiFlags| AbiFlags {
+        soft_float: true,
+        ..abi
+    };
+    let cases = [
+        (ElfArch::Elf64, ElfMachine::X86_64, le(0), vec!["x86_64"]),
+        (
+            ElfArch::Elf32,
+            ElfMachine::X86_64,
+            le(0),
+            vec!["x32", "x86_64"],
+        ),
+        (ElfArch::Elf64, ElfMachine::Arm64, le(0), vec!["aarch64"]),
+        (
+            ElfArch::Elf64,
+            ElfMachine::Arm64,
+            be(0),
+            vec!["aarch64_be", "aarch64"],
+        ),
+        (
+            ElfArch::Elf32,
+            ElfMachine::Arm32,
+            le(0x0500_0400),
+            vec!["armhf", "arm"],
+        ),
+        (
+            ElfArch::Elf32,
+            ElfMachine::Arm32,
+            le(0x0500_0200),
+            vec!["arm"],
+        ),
+        (
+            ElfArch::Elf32,
+            ElfMachine::Arm32,
+            be(0x0500_0200),
+            vec!["armeb", "arm"],
+        ),
+        (
+            ElfArch::Elf32,
+            ElfMachine::Arm32,
+            be(0x0500_0400),
+            vec!["armebhf", "arm"],
+        ),
+        (
+            ElfArch::Elf64,
+            ElfMachine::RiscV64,
+            le(0x4),
+            vec!["riscv64"],
+        ),
+        (
+            ElfArch::Elf64,
+            ElfMachine::RiscV64,
+            le(0x0),
+            vec!["riscv64-sf", "riscv64"],
+        ),
+        (
+            ElfArch::Elf32,
+            ElfMachine::RiscV32,
+            le(0x2),
+            vec!["riscv32-sp", "riscv32"],
+        ),
+        (
+            ElfArch::Elf64,
+            ElfMachine::Mips,
+            be(0),
+            vec!["mips64", "mips"],
+        ),
+        (
+            ElfArch::Elf64,
+            ElfMachine::Mips,
+            le(0),
+            vec!["mips64el", "mips"],
+        ),
+        (ElfArch::Elf32, ElfMachine::Mips, be(0), vec!["mips"]),
+        (
+            ElfArch::Elf32,
+            ElfMachine::Mips,
+            le(0),
+            vec!["mipsel", "mips"],
+        ),
+        (
+            ElfArch::Elf32,
+            ElfMachine::Mips,
+            soft(le(0)),
+            vec!["mipsel-sf", "mips"],
+        ),
+        (
+            ElfArch::Elf64,
+            ElfMachine::Mips,
+            soft(be(0)),
+            vec!["mips64-sf", "mips"],
+        ),
+        (
+            ElfArch::Elf64,
+            ElfMachine::PowerPC,
+            le(0),
+            vec!["powerpc64le", "powerpc"],
+        ),
+        (
+            ElfArch::Elf64,
+            ElfMachine::PowerPC,
+            be(0),
+            vec!["powerpc64", "powerpc"],
+        ),
+        (ElfArch::Elf32, ElfMachine::PowerPC, be(0), vec!["powerpc"]),
+        (
+            ElfArch::Elf32,
+            ElfMachine::PowerPC,
+            soft(be(0)),
+            vec!["powerpc-sf", "powerpc"],
+        ),
+    ];
+    for (arch, machine, abi, expected) in cases {
+        let expected: Vec<_> = expected
+            .iter()
+            .map(|a| PathBuf::from(format!("/etc/ld-musl-{}.path", a)))
+            .collect();
+        assert_eq!(musl_path_files(arch, machine, abi), expected, "{:?}", abi);
+    }
+
+    // soft-float MIPS only says so in .gnu.attributes
+    let mut attrs = vec![b'A'];
+    attrs.extend(15u32.to_le_bytes());
+    attrs.extend(b"gnu\0");
+    attrs.push(1);
+    attrs.extend(7u32.to_le_bytes());
+    attrs.extend([4, 3]);
+    let tree = TempTree::new("musl-abi");
+    let hard = ElfFixture::exe().elf32().machine(EM_MIPS);
+    let soft_float = hard.clone().section(".gnu.attributes", 0x6fff_fff5, &attrs);
+    let abi = |fixture: &ElfFixture| {
+        let map = open_and_map(&tree.write_elf("bin/mips", fixture)).unwrap();
+        let mut scratch = Vec::new();
+        AbiFlags::of(&parse_elf(&map, &mut scratch).unwrap(), &map)
+    };
+    assert!(!abi(&hard).soft_float);
+    assert!(abi(&soft_float).soft_float);
+}
+
+#[test]
+fn test_fail_fast() {
+    let tree = TempTree::new("fail-fast");
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("libfailfast_missing.so")
+            .needed("libfailfast_present.so")
+            .rpath("$ORIGIN/../lib"),
+    );
+    tree.write_elf(
+        "lib/libfailfast_present.so",
+        &ElfFixture::lib().needed("libfailfast_missing_too.so"),
+    );
+
+    let full = rldd_rex(&bin).unwrap();
+    assert_eq!(full.deps.len(), 3);
+    assert_eq!(full.failed_at, None);
+
+    let resolver = Resolver::with_options(RlddOptions {
+        fail_fast: true,
+        ..Default::default()
+    });
+    let info = resolver.rldd_rex(&bin).unwrap();
+    assert_eq!(info.failed_at, Some("libfailfast_missing.so".to_string()));
+    assert_eq!(
+        info.deps,
+        vec![("libfailfast_missing.so".to_string(), DepStatus::NotFound)]
+    );
+    assert_eq!(resolver.parsed.load(Ordering::Relaxed), 0);
+}
+
+#[test]
+fn test_machine_mismatch() {
+    let tree = TempTree::new("machine");
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("libmachine_arm.so")
+            .rpath("$ORIGIN/../lib"),
+    );
+    tree.write_elf(
+        "lib/libmachine_arm.so",
+        &ElfFixture::lib().machine(EM_AARCH64),
+    );
+
+    let info = rldd_rex(&bin).unwrap();
+    assert_eq!(info.deps[0].1, DepStatus::MachineMismatch);
+    assert!(!info.deps[0].1.is_resolved());
+    assert!(info.explain().contains("machine mismatch"));
+}
+
+#[test]
+fn test_soname_usage() {
+    let tree = TempTree::new("usage");
+    tree.write_elf(
+        "bin/one",
+        &ElfFixture::exe()
+            .needed("libusage_shared.so")
+            .needed("libusage_only_one.so"),
+    );
+    tree.write_elf(
+        "usr/bin/two",
+        &ElfFixture::exe().needed("libusage_shared.so"),
+    );
+    tree.write_elf("lib/libusage_shared.so", &ElfFixture::lib());
+    tree.write("etc/notes.txt", b"libusage_shared.so");
+
+    let usage = soname_usage(tree.path());
+    assert_eq!(
+        usage,
+        std::collections::BTreeMap::from([
+            ("libusage_only_one.so".to_string(), 1),
+            ("libusage_shared.so".to_string(), 2),
+        ])
+    );
+}
+
+#[test]
+fn test_arch_mismatch_without_ld_library_path() {
+    let tree = TempTree::new("bitness");
+    let bin = tree.write_elf(
+        "bin/app32",
+        &ElfFixture::exe()
+            .elf32()
+            .machine(EM_X86_64)
+            .needed("libbitness.so")
+            .rpath("$ORIGIN/../opt"),
+    );
+    tree.write_elf("opt/libbitness.so", &ElfFixture::lib());
+
+    let info = rldd_rex(&bin).unwrap();
+    assert_eq!(info.deps[0].1, DepStatus::ArchMismatch);
+    assert!(!info.deps[0].1.is_resolved());
+}
+
+#[test]
+fn test_base_dir() {
+    let tree = TempTree::new("base-dir");
+    tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("libbase_origin.so")
+            .needed("libbase_relative.so")
+            .rpath("$ORIGIN/../plugins:vendor"),
+    );
+    tree.write_elf("plugins/libbase_origin.so", &ElfFixture::lib());
+    tree.write_elf("vendor/libbase_relative.so", &ElfFixture::lib());
+
+    let options = RlddOptions {
+        base_dir: Some(tree.path().to_path_buf()),
+        canonicalize_root: false,
+        ..Default::default()
+    };
+    let info = rldd_rex_with_options("bin/app", options).unwrap();
+    let paths: Vec<_> = info.libs.iter().map(|l| l.path.clone()).collect();
+    assert_eq!(
+        paths,
+        vec![
+            Some(tree.join("bin/../plugins/libbase_origin.so")),
+            Some(tree.join("vendor/libbase_relative.so")),
+        ]
+    );
+}
+
+#[test]
+fn test_power_hwcaps_subdir() {
+    let tree = TempTree::new("power-hwcaps");
+    let lib_dir = tree.mkdir("opt/lib");
+    let conf = tree.write("extra.conf", format!("{}\n", lib_dir.display()).as_bytes());
+    let ppc64 = || ElfFixture::lib().machine(EM_PPC64);
+    tree.write_elf("opt/lib/libpower_hwcaps.so", &ppc64());
+    let optimized = tree.write_elf("opt/lib/glibc-hwcaps/power9/libpower_hwcaps.so", &ppc64());
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .machine(EM_PPC64)
+            .needed("libpower_hwcaps.so"),
+    );
+
+    let options = RlddOptions {
+        extra_ld_conf_files: vec![conf],
+        ..Default::default()
+    };
+    let info = rldd_rex_with_options(&bin, options).unwrap();
+    assert_eq!(info.machine, ElfMachine::PowerPC);
+    assert_eq!(
+        info.deps[0].1,
+        DepStatus::Found(fs::canonicalize(optimized).unwrap())
+    );
+    assert_eq!(info.cpu_baseline, Some("power9".to_string()));
+}
+
+#[test]
+fn test_topo_sorted_closure() {
+    let tree = TempTree::new("topo-chain");
+    tree.write_elf(
+        "opt/libtopo_a.so",
+        &ElfFixture::lib().needed("libtopo_b.so").rpath("$ORIGIN"),
+    );
+    tree.write_elf(
+        "opt/libtopo_b.so",
+        &ElfFixture::lib().needed("libtopo_c.so").rpath("$ORIGIN"),
+    );
+    tree.write_elf("opt/libtopo_c.so", &ElfFixture::lib());
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("libtopo_a.so")
+            .needed("libtopo_c.so")
+            .rpath("$ORIGIN/../opt"),
+    );
+
+    let names: Vec<_> = topo_sorted_closure(&bin)
+        .into_iter()
+        .map(|lib| lib.name)
+        .collect();
+    assert_eq!(names, ["libtopo_c.so", "libtopo_b.so", "libtopo_a.so"]);
+}
+
+#[test]
+fn test_topo_sorted_closure_cycle() {
+    let tree = TempTree::new("topo-cycle");
+    tree.write_elf(
+        "opt/libtopo_x.so",
+        &ElfFixture::lib().needed("libtopo_y.so").rpath("$ORIGIN"),
+    );
+    tree.write_elf(
+        "opt/libtopo_y.so",
+        &ElfFixture::lib().needed("libtopo_x.so").rpath("$ORIGIN"),
+    );
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("libtopo_x.so")
+            .rpath("$ORIGIN/../opt"),
+    );
+
+    let names: Vec<_> = topo_sorted_closure(&bin)
+        .into_iter()
+        .map(|lib| lib.name)
+        .collect();
+    assert_eq!(names, ["libtopo_y.so", "libtopo_x.so"]);
+}
+
+#[test]
+fn test_rpath_runpath_precedence() {
+    let dir = |path: &str, source| SearchDir {
+        path: PathBuf::from(path),
+        source,
+    };
+    let search_dirs = [
+        dir("/usr/lib", SearchSource::System),
+        dir("/env/lib", SearchSource::LdLibraryPath),
+    ];
+    let order = |paths: &[SearchDir]| -> Vec<PathBuf> {
+        search_order(&search_dirs, paths, LdLibraryPathPriority::BeforeRunpath)
+            .map(|d| d.path.clone())
+            .collect()
+    };
+    assert_eq!(
+        order(&[dir("/rpath", SearchSource::Rpath)]),
+        ["/rpath", "/env/lib", "/usr/lib"].map(PathBuf::from)
+    );
+    assert_eq!(
+        order(&[dir("/runpath", SearchSource::Runpath)]),
+        ["/env/lib", "/runpath", "/usr/lib"].map(PathBuf::from)
+    );
+    assert_eq!(
+        order(&[
+            dir("/rpath", SearchSource::Rpath),
+            dir("/runpath", SearchSource::Runpath)
+        ]),
+        ["/env/lib", "/runpath", "/usr/lib"].map(PathBuf::from)
+    );
+
+    // both beat the directories next to the binary
+    let tree = TempTree::new("rpath-precedence");
+    tree.write_elf("lib/libprecedence.so", &ElfFixture::lib());
+    let rpath_lib = tree.write_elf("rpath/libprecedence.so", &ElfFixture::lib());
+    let runpath_lib = tree.write_elf("runpath/libprecedence.so", &ElfFixture::lib());
+    let with_rpath = tree.write_elf(
+        "bin/rpath",
+        &ElfFixture::exe()
+            .needed("libprecedence.so")
+            .rpath("$ORIGIN/../rpath"),
+    );
+    let with_runpath = tree.write_elf(
+        "bin/runpath",
+        &ElfFixture::exe()
+            .needed("libprecedence.so")
+            .runpath("$ORIGIN/../runpath"),
+    );
+    let with_both = tree.write_elf(
+        "bin/both",
+        &ElfFixture::exe()
+            .needed("libprecedence.so")
+            .rpath("$ORIGIN/../rpath")
+            .runpath("$ORIGIN/../runpath"),
+    );
+    let found = |bin: &Path| {
+        let info = rldd_rex(bin).unwrap();
+        (
+            info.libs[0]
+                .path
+                .clone()
+                .map(|p| fs::canonicalize(p).unwrap()),
+            info.libs[0].source,
+        )
+    };
+    assert_eq!(
+        found(&with_rpath),
+        (Some(rpath_lib), Some(SearchSource::Rpath))
+    );
+    assert_eq!(
+        found(&with_runpath),
+        (Some(runpath_lib.clone()), Some(SearchSource::Runpath))
+    );
+
+    // DT_RPATH is not searched at all next to DT_RUNPATH
+    assert_eq!(
+        found(&with_both),
+        (Some(runpath_lib), Some(SearchSource::Runpath))
+    );
+}
+
+#[test]
+fn test_heuristic_only_resolution() {
+    let tree = TempTree::new("heuristic-only");
+    tree.write_elf("lib/libheuristic_bundled.so", &ElfFixture::lib());
+    tree.write_elf("opt/libheuristic_rpath.so", &ElfFixture::lib());
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("libheuristic_bundled.so")
+            .needed("libheuristic_rpath.so")
+            .rpath("$ORIGIN/../opt"),
+    );
+
+    let info = rldd_rex(&bin).unwrap();
+    assert!(info.deps.iter().all(|(_, status)| status.is_resolved()));
+    assert!(info.libs[0].is_heuristic_only());
+    assert!(!info.libs[1].is_heuristic_only());
+    assert!(
+        info.explain()
+            .contains("the loader does not search this dir")
+    );
+    assert_eq!(
+        assert_deps_resolved(&bin),
+        Err(vec!["libheuristic_bundled.so".to_string()])
+    );
+}
+
+#[test]
+fn test_nodeflib_skips_default_dirs() {
+    use goblin::elf::dynamic::DT_FLAGS_1;
+
+    let tree = TempTree::new("nodeflib");
+    let plain = tree.write_elf("bin/plain", &ElfFixture::exe().needed("libc.so.6"));
+    let nodeflib = tree.write_elf(
+        "bin/nodeflib",
+        &ElfFixture::exe()
+            .needed("libc.so.6")
+            .dyn_entry(DT_FLAGS_1, DF_1_NODEFLIB),
+    );
+
+    let is_system = |d: &SearchDir| d.source == SearchSource::System;
+    assert!(rldd_rex(&plain).unwrap().search_dirs.iter().any(is_system));
+    let info = rldd_rex(&nodeflib).unwrap();
+    assert!(!info.search_dirs.iter().any(is_system));
+    assert!(
+        info.search_dirs
+            .iter()
+            .all(|d| d.path != Path::new("/usr/lib"))
+    );
+}
+
+#[test]
+fn test_parsed_binary() {
+    let tree = TempTree::new("parsed-binary");
+    tree.write_elf("opt/libparsed_dep.so", &ElfFixture::lib());
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("libparsed_dep.so")
+            .rpath("$ORIGIN/../opt"),
+    );
+
+    let parsed = ParsedBinary::open(&bin).unwrap();
+    let info = parsed.dependencies().unwrap();
+    assert_eq!(info.deps, rldd_rex(&bin).unwrap().deps);
+    assert_eq!(parsed.elf_type(), info.elf_type);
+    assert_eq!((parsed.arch(), parsed.machine()), (info.arch, info.machine));
+    assert_eq!(parsed.is_pie(), is_pie_executable(&bin));
+    assert_eq!(parsed.needed(), ["libparsed_dep.so"]);
+    assert_eq!(parsed.relro(), (info.relro, info.relro_size));
+
+    assert!(matches!(
+        ParsedBinary::open(tree.write("bin/text", b"not an elf")),
+        Err(RlddError::NotElf)
+    ));
+    let mut truncated = ElfFixture::exe().bytes();
+    truncated.truncate(20);
+    assert!(matches!(
+        ParsedBinary::open(tree.write("bin/truncated", &truncated)),
+        Err(RlddError::ParseFailed(_))
+    ));
+    assert!(matches!(
+        ParsedBinary::open(tree.join("bin/missing")),
+        Err(RlddError::Io(e)) if e.kind() == io::ErrorKind::NotFound
+    ));
+}
+
+#[test]
+fn test_sysroot() {
+    let root = TempTree::new("sysroot");
+    let sysroot = fs::canonicalize(root.path()).unwrap();
+    let system = root.write_elf("lib/libsysroot_system.so", &ElfFixture::lib());
+    let conf_dir = root.mkdir("opt/conf");
+    root.write("etc/ld.so.conf", b"include /etc/ld.so.conf.d/*.conf\n");
+    root.write("etc/ld.so.conf.d/target.conf", b"/opt/conf\n");
+    let conf = root.write_elf("opt/conf/libsysroot_conf.so", &ElfFixture::lib());
+    let rpath = root.write_elf("vendor/lib/libsysroot_rpath.so", &ElfFixture::lib());
+    let origin = root.write_elf("usr/plugins/libsysroot_origin.so", &ElfFixture::lib());
+    let bin = root.write_elf(
+        "usr/bin/app",
+        &ElfFixture::exe()
+            .needed("libsysroot_system.so")
+            .needed("libsysroot_conf.so")
+            .needed("libsysroot_rpath.so")
+            .needed("libsysroot_origin.so")
+            .rpath("/vendor/lib:$ORIGIN/../plugins"),
+    );
+
+    let info = rldd_rex_with_sysroot(&bin, &sysroot).unwrap();
+    let paths: Vec<_> = info
+        .libs
+        .iter()
+        .map(|lib| lib.path.clone().map(|p| fs::canonicalize(p).unwrap()))
+        .collect();
+    assert_eq!(
+        paths,
+        [system, conf, rpath, origin].map(|p| Some(fs::canonicalize(p).unwrap()))
+    );
+    assert_eq!(info.libs[0].source, Some(SearchSource::System));
+    assert!(info.search_dirs.contains(&SearchDir {
+        path: fs::canonicalize(conf_dir).unwrap(),
+        source: SearchSource::LdSoConf,
+    }));
+    assert!(
+        info.search_dirs
+            .iter()
+            .all(|d| d.source != SearchSource::System || d.path.starts_with(&sysroot))
+    );
+
+    // without the sysroot none of it is found on the host
+    assert!(
+        rldd_rex(&bin).unwrap().deps[..3]
+            .iter()
+            .all(|(_, status)| *status == DepStatus::NotFound)
+    );
+}
+
+#[test]
+fn test_search_config_summary() {
+    let tree = TempTree::new("search-config");
+    let conf = tree.write("extra.conf", b"");
+    let bin = tree.write_elf("bin/app", &ElfFixture::exe().needed("libc.so.6"));
+
+    let options = RlddOptions {
+        extra_ld_conf_files: vec![conf.clone()],
+        cache_dir_listings: true,
+        sysroot: Some(tree.path().to_path_buf()),
+        ..Default::default()
+    };
+    let config = rldd_rex_with_options(&bin, options)
+        .unwrap()
+        .search_config_summary;
+    assert_eq!(
+        config,
+        SearchConfig {
+            // other tests set LD_LIBRARY_PATH concurrently
+            ld_library_path: config.ld_library_path.clone(),
+            ld_so_conf: true,
+            extra_ld_conf_files: vec![conf],
+            musl_path_file: None,
+            default_dirs: true,
+            bin_dir_heuristic: true,
+            dir_contents: false,
+            cache_dir_listings: true,
+            sysroot: Some(tree.path().to_path_buf()),
+            base_dir: None,
+            extra_dirs: Vec::new(),
+        }
+    );
+
+    let options = RlddOptions {
+        force_libc: Some(LibcFamily::Musl),
+        ..Default::default()
+    };
+    let config = rldd_rex_with_options(&bin, options)
+        .unwrap()
+        .search_config_summary;
+    assert!(!config.ld_so_conf);
+    assert_eq!(config.sysroot, None);
+}
+
+#[test]
+fn test_search_dirs_for() {
+    let tree = TempTree::new("search-dirs-for");
+    let bin = tree.write_elf("bin/app", &ElfFixture::exe().needed("libc.so.6"));
+
+    let dirs = search_dirs_for(&bin).unwrap();
+    let usr_lib = fs::canonicalize("/usr/lib").unwrap();
+    let bin_dir = fs::canonicalize(tree.join("bin")).unwrap();
+    assert!(dirs.contains(&usr_lib));
+    assert!(dirs.contains(&bin_dir));
+    let pos = |dir: &PathBuf| dirs.iter().position(|d| d == dir).unwrap();
+    assert!(pos(&usr_lib) < pos(&bin_dir));
+    assert_eq!(dirs.len(), dirs.iter().collect::<HashSet<_>>().len());
+
+    assert!(search_dirs_for(tree.join("bin/missing")).is_err());
+}
+
+#[test]
+fn test_needed_with_relative_path() {
+    let tree = TempTree::new("needed-relative");
+    let local = tree.write_elf("bin/libneeded_local.so", &ElfFixture::lib());
+    let nested = tree.write_elf("bin/sub/libneeded_nested.so", &ElfFixture::lib());
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("./libneeded_local.so")
+            .needed("sub/libneeded_nested.so")
+            .needed("./libneeded_missing.so"),
+    );
+
+    let info = rldd_rex(&bin).unwrap();
+    let bin_dir = fs::canonicalize(tree.join("bin")).unwrap();
+    assert_eq!(
+        info.deps,
+        vec![
+            (
+                "./libneeded_local.so".to_string(),
+                DepStatus::Found(fs::canonicalize(local).unwrap())
+            ),
+            (
+                "sub/libneeded_nested.so".to_string(),
+                DepStatus::Found(fs::canonicalize(nested).unwrap())
+            ),
+            ("./libneeded_missing.so".to_string(), DepStatus::NotFound),
+        ]
+    );
+    assert_eq!(info.libs[0].source, Some(SearchSource::Direct));
+    assert_eq!(info.libs[0].dir, Some(bin_dir));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_info_serde_round_trip() {
+    let tree = TempTree::new("info-serde");
+    tree.write_elf("opt/libserde_dep.so", &ElfFixture::lib());
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("libserde_dep.so")
+            .needed("libserde_missing.so")
+            .rpath("$ORIGIN/../opt"),
+    );
+
+    let info = rldd_rex(&bin).unwrap();
+    let json = serde_json::to_value(&info).unwrap();
+    assert_eq!(json["arch"], "Elf64");
+    assert_eq!(json["machine"], "X86_64");
+    assert_eq!(
+        json["deps"],
+        serde_json::json!([
+            {
+                "name": "libserde_dep.so",
+                "status": "Found",
+                "path": info.libs[0].path,
+            },
+            {"name": "libserde_missing.so", "status": "NotFound", "path": null},
+        ])
+    );
+
+    let back: RlddRexInfo = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(back.deps, info.deps);
+    assert_eq!(back.elf_type, info.elf_type);
+    assert_eq!(serde_json::to_value(&back).unwrap(), json);
+}
+
+#[test]
+fn test_duplicate_needed() {
+    let tree = TempTree::new("duplicate-needed");
+    tree.write_elf(
+        "opt/libdup_mid.so",
+        &ElfFixture::lib()
+            .needed("libdup_leaf.so")
+            .needed("libdup_leaf.so")
+            .rpath("$ORIGIN"),
+    );
+    tree.write_elf("opt/libdup_leaf.so", &ElfFixture::lib());
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("libdup_mid.so")
+            .needed("libdup_leaf.so")
+            .rpath("$ORIGIN/../opt"),
+    );
+
+    let info = rldd_rex(&bin).unwrap();
+    assert_eq!(info.duplicate_needed, ["libdup_leaf.so"]);
+    assert!(info.warnings.iter().any(|w| w.contains("libdup_mid.so")));
+    // still resolved once each
+    assert_eq!(info.deps.len(), 2);
+}
+
+#[test]
+fn test_invalid_rpath_dirs() {
+    let tree = TempTree::new("invalid-rpath");
+    tree.write_elf("opt/libvalid.so", &ElfFixture::lib());
+    tree.write("opt/not-a-dir", b"");
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("libvalid.so")
+            .rpath("/nonexistent/stale-build/lib:$ORIGIN/../opt:$ORIGIN/../opt/not-a-dir"),
+    );
+
+    let info = rldd_rex(&bin).unwrap();
+    assert!(info.deps[0].1.is_resolved());
+    assert_eq!(info.invalid_rpath_dirs.len(), 2);
+    assert_eq!(
+        info.invalid_rpath_dirs[0],
+        PathBuf::from("/nonexistent/stale-build/lib")
+    );
+    assert!(info.invalid_rpath_dirs[1].ends_with("not-a-dir"));
+}
+
+#[test]
+fn test_dependency_cycles() {
+    let tree = TempTree::new("dep-cycle");
+    tree.write_elf(
+        "opt/libcycle_a.so",
+        &ElfFixture::lib().needed("libcycle_b.so").rpath("$ORIGIN"),
+    );
+    let lib_b = tree.write_elf(
+        "opt/libcycle_b.so",
+        &ElfFixture::lib().needed("libcycle_a.so").rpath("$ORIGIN"),
+    );
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("libcycle_a.so")
+            .rpath("$ORIGIN/../opt"),
+    );
+
+    let info = rldd_rex(&bin).unwrap();
+    assert_eq!(info.deps.len(), 2);
+    assert!(info.deps.iter().all(|(_, status)| status.is_resolved()));
+    assert_eq!(
+        info.cycles,
+        [["libcycle_a.so", "libcycle_b.so", "libcycle_a.so"]]
+    );
+    assert!(info.warnings.iter().any(|w| w.contains("dependency cycle")));
+
+    // the root itself needed again by one of its dependencies
+    let info = rldd_rex(&lib_b).unwrap();
+    assert_eq!(
+        info.cycles,
+        [["libcycle_b.so", "libcycle_a.so", "libcycle_b.so"]]
+    );
+}
+
+#[test]
+fn test_rldd_rex_tree() {
+    let tree = TempTree::new("dep-tree");
+    tree.write_elf("opt/libtree_mid.so", &ElfFixture::lib().needed("libc.so.6"));
+    tree.write_elf(
+        "opt/libtree_other.so",
+        &ElfFixture::lib().needed("libc.so.6"),
+    );
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("libtree_mid.so")
+            .needed("libtree_other.so")
+            .needed("libtree_missing.so")
+            .rpath("$ORIGIN/../opt"),
+    );
+
+    let root = rldd_rex_tree(&bin);
+    assert_eq!(root.name, "app");
+    let names: Vec<_> = root.children.iter().map(|n| n.name.as_str()).collect();
+    assert_eq!(
+        names,
+        ["libtree_mid.so", "libtree_other.so", "libtree_missing.so"]
+    );
+
+    let libc = &root.children[0].children[0];
+    assert_eq!(libc.name, "libc.so.6");
+    assert!(libc.status.is_resolved());
+    assert!(!libc.back_ref);
+    // second time around it is only a reference
+    let again = &root.children[1].children[0];
+    assert_eq!(again.name, "libc.so.6");
+    assert!(again.back_ref);
+    assert!(again.children.is_empty());
+    assert_eq!(root.children[2].status, DepStatus::NotFound);
+}
+
+#[test]
+fn test_dep_sort() {
+    let tree = TempTree::new("dep-sort");
+    for name in ["libsort_b.so", "libsort_c.so"] {
+        tree.write_elf(&format!("opt/zz/{}", name), &ElfFixture::lib());
+    }
+    tree.write_elf("opt/aa/libsort_d.so", &ElfFixture::lib());
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("libsort_c.so")
+            .needed("libsort_a_missing.so")
+            .needed("libsort_b.so")
+            .needed("libsort_d.so")
+            .rpath("$ORIGIN/../opt/zz:$ORIGIN/../opt/aa"),
+    );
+
+    let names = |sort| {
+        let options = RlddOptions {
+            sort,
+            ..Default::default()
+        };
+        let info = rldd_rex_with_options(&bin, options).unwrap();
+        for (lib, (name, _)) in info.libs.iter().zip(&info.deps) {
+            assert_eq!(&lib.name, name);
+        }
+        info.deps
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        names(DepSort::None),
+        [
+            "libsort_c.so",
+            "libsort_a_missing.so",
+            "libsort_b.so",
+            "libsort_d.so"
+        ]
+    );
+    assert_eq!(
+        names(DepSort::BySoname),
+        [
+            "libsort_a_missing.so",
+            "libsort_b.so",
+            "libsort_c.so",
+            "libsort_d.so"
+        ]
+    );
+    assert_eq!(
+        names(DepSort::ByPath),
+        [
+            "libsort_d.so",
+            "libsort_b.so",
+            "libsort_c.so",
+            "libsort_a_missing.so"
+        ]
+    );
+}
+
+#[test]
+fn test_found_dir_and_source() {
+    let tree = TempTree::new("found-source");
+    let vendor = tree.mkdir("vendor");
+    let runpath = tree.mkdir("runpath");
+    let conf_dir = tree.mkdir("conf");
+    tree.write_elf("vendor/libsource_rpath.so", &ElfFixture::lib());
+    tree.write_elf("runpath/libsource_runpath.so", &ElfFixture::lib());
+    tree.write_elf("conf/libsource_conf.so", &ElfFixture::lib());
+    let conf = tree.write("extra.conf", format!("{}\n", conf_dir.display()).as_bytes());
+    let with_rpath = tree.write_elf(
+        "bin/rpath",
+        &ElfFixture::exe()
+            .needed("libsource_rpath.so")
+            .needed("libsource_conf.so")
+            .rpath(vendor.to_str().unwrap()),
+    );
+    let with_runpath = tree.write_elf(
+        "bin/runpath",
+        &ElfFixture::exe()
+            .needed("libsource_runpath.so")
+            .runpath(runpath.to_str().unwrap()),
+    );
+    let found = |bin: &Path| {
+        let options = RlddOptions {
+            extra_ld_conf_files: vec![conf.clone()],
+            ..Default::default()
+        };
+        rldd_rex_with_options(bin, options)
+            .unwrap()
+            .libs
+            .into_iter()
+            .map(|lib| (lib.dir, lib.source))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        found(&with_rpath),
+        [
+            (Some(vendor), Some(SearchSource::Rpath)),
+            (Some(conf_dir), Some(SearchSource::LdSoConf)),
+        ]
+    );
+    assert_eq!(
+        found(&with_runpath),
+        [(Some(runpath), Some(SearchSource::Runpath))]
+    );
+}
+
+#[test]
+fn test_hash_style() {
+    let tree = TempTree::new("hash-style");
+    tree.write_elf(
+        "opt/libhash_gnu.so",
+        &ElfFixture::lib()
+            .export("gnu_one")
+            .export("gnu_two")
+            .gnu_hash(),
+    );
+    tree.write_elf("opt/libhash_sysv.so", &ElfFixture::lib().export("sysv_one"));
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("libhash_gnu.so")
+            .needed("libhash_sysv.so")
+            .rpath("$ORIGIN/../opt"),
+    );
+
+    let info = rldd_rex(&bin).unwrap();
+    let styles: Vec<_> = info
+        .libs
+        .iter()
+        .map(|lib| (lib.hash_style, lib.dynsym_count))
+        .collect();
+    // the null symbol counts too
+    assert_eq!(styles, [(HashStyle::Gnu, 3), (HashStyle::Sysv, 2)]);
+    assert_eq!((info.hash_style, info.dynsym_count), (HashStyle::None, 0));
+}
+
+#[test]
+fn test_real_path_of_symlinked_lib() {
+    let tree = TempTree::new("real-path");
+    let real = tree.write_elf("opt/libreal_path.so.1.2.3", &ElfFixture::lib());
+    std::os::unix::fs::symlink("libreal_path.so.1.2.3", tree.join("opt/libreal_path.so.1"))
+        .unwrap();
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("libreal_path.so.1")
+            .rpath(tree.join("opt").to_str().unwrap()),
+    );
+
+    let info = rldd_rex(&bin).unwrap();
+    let link = tree.join("opt/libreal_path.so.1");
+    assert_eq!(info.deps[0].1, DepStatus::Found(link.clone()));
+    assert_eq!(
+        info.libs[0].real_path,
+        Some(fs::canonicalize(&real).unwrap())
+    );
+    assert!(
+        info.explain()
+            .contains(&format!("{} -> {}", link.display(), real.display()))
+    );
+}
+
+#[test]
+fn test_extra_search_dirs() {
+    let tree = TempTree::new("extra-dirs");
+    let plugin_dir = tree.mkdir("plugins");
+    let conf_dir = tree.mkdir("opt/lib");
+    let conf = tree.write("extra.conf", format!("{}\n", conf_dir.display()).as_bytes());
+    let plugin_lib = tree.write_elf("plugins/libplugin_host.so", &ElfFixture::lib());
+    let bin = tree.write_elf("bin/app", &ElfFixture::exe().needed("libplugin_host.so"));
+
+    assert!(!rldd_rex(&bin).unwrap().deps[0].1.is_resolved());
+    let info = rldd_rex_with_paths(
+        &bin,
+        std::slice::from_ref(&plugin_dir),
+        ExtraDirsPosition::Append,
+    )
+    .unwrap();
+    assert_eq!(info.deps[0].1, DepStatus::Found(plugin_lib.clone()));
+    assert_eq!(info.libs[0].source, Some(SearchSource::Extra));
+
+    // the same soname in an ld.so.conf dir decides who wins
+    tree.write_elf("opt/lib/libplugin_host.so", &ElfFixture::lib());
+    let source = |position| {
+        let options = RlddOptions {
+            extra_ld_conf_files: vec![conf.clone()],
+            extra_dirs: vec![plugin_dir.clone()],
+            extra_dirs_position: position,
+            ..Default::default()
+        };
+        rldd_rex_with_options(&bin, options).unwrap().libs[0].source
+    };
+    assert_eq!(
+        source(ExtraDirsPosition::Append),
+        Some(SearchSource::LdSoConf)
+    );
+    assert_eq!(
+        source(ExtraDirsPosition::Prepend),
+        Some(SearchSource::Extra)
+    );
+}
+
+#[test]
+fn test_ld_library_path_priority() {
+    let dir = |path: &str, source| SearchDir {
+        path: PathBuf::from(path),
+        source,
+    };
+    let search_dirs = [
+        dir("/env/lib", SearchSource::LdLibraryPath),
+        dir("/usr/lib", SearchSource::System),
+    ];
+    let order = |paths: &[SearchDir], priority| -> Vec<PathBuf> {
+        search_order(&search_dirs, paths, priority)
+            .map(|d| d.path.clone())
+            .collect()
+    };
+    let runpath = [dir("/runpath", SearchSource::Runpath)];
+    assert_eq!(
+        order(&runpath, LdLibraryPathPriority::default()),
+        ["/env/lib", "/runpath", "/usr/lib"].map(PathBuf::from)
+    );
+    assert_eq!(
+        order(&runpath, LdLibraryPathPriority::AfterRpathOnly),
+        ["/runpath", "/env/lib", "/usr/lib"].map(PathBuf::from)
+    );
+    // RPATH comes first either way
+    let rpath = [dir("/rpath", SearchSource::Rpath)];
+    for priority in [
+        LdLibraryPathPriority::BeforeRunpath,
+        LdLibraryPathPriority::AfterRpathOnly,
+    ] {
+        assert_eq!(
+            order(&rpath, priority),
+            ["/rpath", "/env/lib", "/usr/lib"].map(PathBuf::from)
+        );
+    }
+}
+
+#[test]
+fn test_open_and_parse_errors() {
+    let tree = TempTree::new("rldd-errors");
+    let missing = tree.path().join("bin/missing");
+    let script = tree.write("bin/script.sh", b"#!/bin/sh\nexit 0\n");
+    let mut truncated = ElfFixture::exe().bytes();
+    truncated.truncate(20);
+    let truncated = tree.write("bin/truncated", &truncated);
+
+    assert!(matches!(
+        rldd_rex(&missing),
+        Err(RlddError::Io(e)) if e.kind() == io::ErrorKind::NotFound
+    ));
+    assert!(matches!(rldd_rex(&script), Err(RlddError::NotElf)));
+    assert!(matches!(
+        rldd_rex(&truncated),
+        Err(RlddError::ParseFailed(_))
+    ));
+
+    // the lenient wrapper keeps the old empty result
+    for path in [&missing, &script, &truncated] {
+        let info = rldd_rex_lenient(path).unwrap();
+        assert_eq!(info.elf_type, ElfType::Invalid);
+        assert!(info.deps.is_empty());
+    }
+}
+
+#[test]
+fn test_analyze_bundle() {
+    let host = TempTree::new("bundle-host");
+    let host_lib = host.write_elf("lib/libbundle_leak.so", &ElfFixture::lib());
+    let tree = TempTree::new("bundle");
+    tree.write_elf("usr/lib/libbundle_own.so", &ElfFixture::lib());
+    // also in an ld.so.conf dir, the bundled copy has to win
+    host.write_elf("conf/libbundle_own.so", &ElfFixture::lib());
+    let conf = host.write(
+        "extra.conf",
+        format!("{}\n", host.join("conf").display()).as_bytes(),
+    );
+    let rpath = host.join("lib").display().to_string();
+    let good = tree.write_elf(
+        "usr/bin/good",
+        &ElfFixture::exe().needed("libbundle_own.so").rpath(&rpath),
+    );
+    let leaky = tree.write_elf(
+        "usr/bin/leaky",
+        &ElfFixture::exe()
+            .needed("libbundle_own.so")
+            .needed("libbundle_leak.so")
+            .rpath(&rpath),
+    );
+    tree.write("usr/bin/launcher.sh", b"#!/bin/sh\n");
+    std::os::unix::fs::symlink("usr/bin/good", tree.join("AppRun")).unwrap();
+
+    assert_eq!(analyze_bundle(tree.path()).binaries.len(), 2);
+    let options = RlddOptions {
+        extra_ld_conf_files: vec![conf],
+        ..Default::default()
+    };
+    let report = Resolver::with_options(options).analyze_bundle(tree.path());
+    let paths: Vec<_> = report.binaries.iter().map(|b| b.path.clone()).collect();
+    let root = fs::canonicalize(tree.path()).unwrap();
+    let in_root = |p: &Path| root.join(p.strip_prefix(tree.path()).unwrap());
+    assert_eq!(paths, [in_root(&good), in_root(&leaky)]);
+    assert!(!report.is_self_contained());
+
+    assert!(report.binaries[0].escaping.is_empty());
+    let escaping: Vec<_> = report.binaries[1]
+        .escaping
+        .iter()
+        .map(|lib| lib.real_path.clone().unwrap())
+        .collect();
+    assert_eq!(escaping, [fs::canonicalize(host_lib).unwrap()]);
+}
+
+#[test]
+fn test_on_warning_hook() {
+    let tree = TempTree::new("on-warning");
+    let bin = tree.write_elf("bin/app", &ElfFixture::exe().rpath("$ORIGIN/../dangling"));
+    std::os::unix::fs::symlink(tree.join("gone"), tree.join("dangling")).unwrap();
+    let missing_conf = tree.join("missing.conf");
+
+    let warnings = Arc::new(Mutex::new(Vec::new()));
+    let sink = warnings.clone();
+    let options = RlddOptions {
+        on_warning: Some(Hook::new(move |w: &Warning| {
+            sink.lock().unwrap().push(w.message.clone())
+        })),
+        extra_ld_conf_files: vec![missing_conf.clone()],
+        ..Default::default()
+    };
+
+    let info = rldd_rex_with_options(&bin, options).unwrap();
+    let warnings = warnings.lock().unwrap();
+    assert!(
+        warnings
+            .iter()
+            .any(|w| w.contains("Fail to read") && w.contains("missing.conf")),
+        "{:?}",
+        warnings
+    );
+    // walk warnings reach the hook as well as info.warnings
+    assert!(warnings.iter().any(|w| w.contains("dangling symlink")));
+    assert!(info.warnings.iter().all(|w| warnings.contains(w)));
+}
+
+#[test]
+fn test_parallel_matches_serial() {
+    let tree = TempTree::new("parallel");
+    let mut exe = ElfFixture::exe().rpath("$ORIGIN/../lib");
+    for i in 0..48 {
+        let mut lib = ElfFixture::lib().rpath("$ORIGIN");
+        // a few shared leaves and a missing one, reached from many places
+        for leaf in [i % 5, (i * 7) % 11] {
+            lib = lib.needed(&format!("libpar_leaf{}.so", leaf));
+        }
+        if i % 6 == 0 {
+            lib = lib.needed("libpar_missing.so");
+        }
+        tree.write_elf(&format!("lib/libpar{}.so", i), &lib);
+        exe = exe.needed(&format!("libpar{}.so", i));
+    }
+    for leaf in 0..11 {
+        tree.write_elf(&format!("lib/libpar_leaf{}.so", leaf), &ElfFixture::lib());
+    }
+    let bin = tree.write_elf("bin/app", &exe);
+
+    let run = |parallel| {
+        let resolver = Resolver::with_options(RlddOptions {
+            parallel,
+            ..Default::default()
+        });
+        let info = resolver.rldd_rex(&bin).unwrap();
+        let libs: Vec<_> = info
+            .libs
+            .iter()
+            .map(|lib| (lib.name.clone(), lib.path.clone(), lib.depth, lib.source))
+            .collect();
+        (info.deps, libs, resolver.parsed.load(Ordering::Relaxed))
+    };
+    let serial = run(false);
+    assert_eq!(serial.0.len(), 48 + 11 + 1);
+    for _ in 0..4 {
+        assert_eq!(run(true), serial);
+    }
+}
+
+#[test]
+fn test_diamond_parsed_once() {
+    let tree = TempTree::new("diamond");
+    tree.write_elf(
+        "lib/libdiamond_a.so",
+        &ElfFixture::lib().needed("libdiamond_shared.so.1"),
+    );
+    tree.write_elf(
+        "lib/libdiamond_b.so",
+        &ElfFixture::lib().needed("libdiamond_alias.so"),
+    );
+    let shared = tree.write_elf("lib/libdiamond_shared.so.1", &ElfFixture::lib());
+    std::os::unix::fs::symlink(&shared, tree.join("lib/libdiamond_alias.so")).unwrap();
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("libdiamond_a.so")
+            .needed("libdiamond_b.so")
+            .rpath("$ORIGIN/../lib"),
+    );
+
+    let resolver = Resolver::new();
+    let info = resolver.rldd_rex(&bin).unwrap();
+    assert_eq!(info.deps.len(), 4);
+    assert!(info.deps.iter().all(|(_, status)| status.is_resolved()));
+    // two names, one inode: mapped and parsed a single time
+    assert_eq!(resolver.parsed.load(Ordering::Relaxed), 3);
+    assert_eq!(resolver.objects.lock().unwrap().len(), 3);
+}
+
+#[test]
+fn test_rldd_rex_from_bytes() {
+    let ls = fs::canonicalize("/bin/ls").unwrap();
+    let data = fs::read(&ls).unwrap();
+    let from_path = rldd_rex(&ls).unwrap();
+    let from_bytes = rldd_rex_from_bytes(&data, ls.parent()).unwrap();
+    assert_eq!(from_bytes.deps, from_path.deps);
+    assert_eq!(from_bytes.elf_type, from_path.elf_type);
+
+    let tree = TempTree::new("from-bytes");
+    tree.write_elf("lib/libfrom_bytes.so", &ElfFixture::lib());
+    let exe = ElfFixture::exe()
+        .needed("libfrom_bytes.so")
+        .rpath("$ORIGIN/../lib")
+        .bytes();
+    let bin_dir = tree.mkdir("bin");
+    let info = rldd_rex_from_bytes(&exe, Some(&bin_dir)).unwrap();
+    assert!(info.deps[0].1.is_resolved());
+    // no location, no $ORIGIN
+    let info = rldd_rex_from_bytes(&exe, None).unwrap();
+    assert_eq!(info.deps[0].1, DepStatus::NotFound);
+
+    assert!(matches!(
+        rldd_rex_from_bytes(b"#!/bin/sh\n", None),
+        Err(RlddError::NotElf)
+    ));
+}
+
+#[test]
+fn test_glibc_interp_reported() {
+    let tree = TempTree::new("glibc-interp");
+    let loader = tree.write_elf("lib64/ld-linux-x86-64.so.2", &ElfFixture::lib());
+    tree.write_elf("lib/libglibc_interp.so", &ElfFixture::lib());
+    let mut exe = ElfFixture::exe()
+        .needed("libglibc_interp.so")
+        .rpath("$ORIGIN/../lib");
+    exe.interp = Some(loader.display().to_string());
+    let bin = tree.write_elf("bin/app", &exe);
+
+    let info = rldd_rex(&bin).unwrap();
+    assert_eq!(info.deps.len(), 2);
+    assert!(info.deps[0].1.is_resolved());
+    // listed last, as ldd does
+    assert_eq!(
+        info.deps[1],
+        (
+            "ld-linux-x86-64.so.2".to_string(),
+            DepStatus::Found(fs::canonicalize(&loader).unwrap())
+        )
+    );
+    assert_eq!(info.libs[1].source, Some(SearchSource::Interp));
+
+    // a forced libc family decides the position, not the loader's name
+    let options = RlddOptions {
+        force_libc: Some(LibcFamily::Musl),
+        ..Default::default()
+    };
+    let info = rldd_rex_with_options(&bin, options).unwrap();
+    assert_eq!(info.deps[0].0, "ld-linux-x86-64.so.2");
+    assert_eq!(info.libs[0].source, Some(SearchSource::Interp));
+
+    // pulled in through DT_NEEDED already, not listed twice
+    tree.write_elf(
+        "lib/libglibc_interp.so",
+        &ElfFixture::lib()
+            .needed("ld-linux-x86-64.so.2")
+            .rpath("$ORIGIN/../lib64"),
+    );
+    let info = rldd_rex(&bin).unwrap();
+    let names: Vec<_> = info.deps.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, ["ld-linux-x86-64.so.2", "libglibc_interp.so"]);
+    assert_ne!(info.libs[0].source, Some(SearchSource::Interp));
+
+    // inside a sysroot, absolute loader symlinks stay inside it
+    let sysroot = TempTree::new("glibc-interp-sysroot");
+    let real = sysroot.write_elf("usr/lib/ld-linux-x86-64.so.2", &ElfFixture::lib());
+    sysroot.mkdir("lib64");
+    std::os::unix::fs::symlink(
+        "/usr/lib/ld-linux-x86-64.so.2",
+        sysroot.join("lib64/ld-linux-x86-64.so.2"),
+    )
+    .unwrap();
+    let mut exe = ElfFixture::exe();
+    exe.interp = Some("/lib64/ld-linux-x86-64.so.2".into());
+    let bin = sysroot.write_elf("usr/bin/app", &exe);
+    let info = rldd_rex_with_sysroot(&bin, sysroot.path()).unwrap();
+    assert_eq!(info.deps.len(), 1);
+    assert_eq!(info.deps[0].1, DepStatus::Found(real));
+}
+
+#[test]
+fn test_info_display() {
+    let ls = rldd_rex("/bin/ls").unwrap();
+    assert!(ls.to_string().contains("libc.so.6 => /"), "{}", ls);
+
+    let tree = TempTree::new("display");
+    let lib = tree.write_elf("lib/libdisplay.so", &ElfFixture::lib());
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("libdisplay.so")
+            .needed("libdisplay_missing.so")
+            .rpath(&tree.join("lib").display().to_string()),
+    );
+    let info = rldd_rex(&bin).unwrap();
+    assert_eq!(
+        info.to_string(),
+        format!(
+            "Elf64 X86_64 (Dynamic)\n\tlibdisplay.so => {}\n\tlibdisplay_missing.so => not found\n",
+            lib.display()
+        )
+    );
+    assert!(
+        info.format_colored()
+            .contains("libdisplay_missing.so => \x1b[1;31mnot found\x1b[0m")
+    );
+}
+
+#[test]
+fn test_dep_iterators() {
+    let tree = TempTree::new("dep-iter");
+    let present = tree.write_elf("lib/libiter_present.so", &ElfFixture::lib());
+    tree.write_elf("lib/libiter_arm.so", &ElfFixture::lib().machine(EM_AARCH64));
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("libiter_present.so")
+            .needed("libiter_absent.so")
+            .needed("libiter_arm.so")
+            .rpath(&tree.join("lib").display().to_string()),
+    );
+
+    let info = rldd_rex(&bin).unwrap();
+    assert_eq!(info.iter().count(), 3);
+    assert_eq!(
+        info.iter().nth(1),
+        Some(("libiter_absent.so", &DepStatus::NotFound))
+    );
+    assert_eq!(info.missing().count(), 2);
+    assert_eq!(
+        info.missing().collect::<Vec<_>>(),
+        ["libiter_absent.so", "libiter_arm.so"]
+    );
+    assert_eq!(
+        info.resolved().collect::<Vec<_>>(),
+        [("libiter_present.so", present.as_path())]
+    );
+}
+
+#[test]
+fn test_direct_deps() {
+    let ls = ParsedBinary::open(fs::canonicalize("/bin/ls").unwrap()).unwrap();
+    let info = ls.dependencies().unwrap();
+    assert_eq!(info.direct_deps.len(), ls.needed().len());
+    assert_eq!(info.direct_deps, ls.needed());
+
+    let tree = TempTree::new("direct-deps");
+    tree.write_elf(
+        "lib/libdirect_mid.so",
+        &ElfFixture::lib().needed("libdirect_leaf.so"),
+    );
+    tree.write_elf("lib/libdirect_leaf.so", &ElfFixture::lib());
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("libdirect_mid.so")
+            .needed("libdirect_gone.so")
+            .rpath("$ORIGIN/../lib"),
+    );
+    let info = rldd_rex(&bin).unwrap();
+    assert_eq!(info.deps.len(), 3);
+    assert_eq!(info.direct_deps, ["libdirect_mid.so", "libdirect_gone.so"]);
+}
+
+#[test]
+fn test_dep_soname() {
+    let tree = TempTree::new("dep-soname");
+    tree.write_elf(
+        "lib/libsoname_dev.so",
+        &ElfFixture::lib().soname("libsoname_dev.so.2"),
+    );
+    tree.write_elf("lib/libsoname_none.so", &ElfFixture::lib());
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("libsoname_dev.so")
+            .needed("libsoname_none.so")
+            .rpath("$ORIGIN/../lib"),
+    );
+    let info = rldd_rex(&bin).unwrap();
+    assert_eq!(info.libs[0].soname.as_deref(), Some("libsoname_dev.so.2"));
+    assert_eq!(info.libs[1].soname, None);
+    assert!(info.explain().contains("[SONAME libsoname_dev.so.2]"));
+
+    // a system library, against its own dynamic section
+    let ls = rldd_rex("/bin/ls").unwrap();
+    let libc = ls.libs.iter().find(|lib| lib.name == "libc.so.6").unwrap();
+    let data = fs::read(libc.path.as_ref().unwrap()).unwrap();
+    let elf = Elf::parse(&data).unwrap();
+    assert_eq!(libc.soname.as_deref(), elf.soname);
+    assert_eq!(libc.soname.as_deref(), Some("libc.so.6"));
+}
+
+#[test]
+fn test_version_requirements() {
+    let info = rldd_rex("/bin/ls").unwrap();
+    let libc = &info.version_requirements["libc.so.6"];
+    assert!(libc.iter().all(|v| v.starts_with("GLIBC_")));
+    // the x86_64 baseline, every binary linked against glibc there needs it
+    if cfg!(target_arch = "x86_64") {
+        assert!(libc.iter().any(|v| v == "GLIBC_2.2.5"), "{:?}", libc);
+    }
+
+    // no .gnu.version_r at all
+    let tree = TempTree::new("verneed");
+    let bin = tree.write_elf("bin/app", &ElfFixture::exe().needed("libverneed.so"));
+    assert!(rldd_rex(&bin).unwrap().version_requirements.is_empty());
+}
+
+#[test]
+fn test_validate_symbols() {
+    let tree = TempTree::new("validate-symbols");
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("libvalidate_fake.so")
+            .needed("libvalidate_arm.so")
+            .import("validate_present")
+            .import("validate_expected")
+            .import("validate_arm_only")
+            .rpath("$ORIGIN/../lib"),
+    );
+    // a stand-in that lacks one of the expected symbols
+    tree.write_elf(
+        "lib/libvalidate_fake.so",
+        &ElfFixture::lib().export("validate_present"),
+    );
+    // can't be loaded, so its exports don't count
+    tree.write_elf(
+        "lib/libvalidate_arm.so",
+        &ElfFixture::lib()
+            .machine(EM_AARCH64)
+            .export("validate_arm_only"),
+    );
+
+    assert!(rldd_rex(&bin).unwrap().unresolved_symbols.is_empty());
+    let options = RlddOptions {
+        validate_symbols: true,
+        ..Default::default()
+    };
+    let info = rldd_rex_with_options(&bin, options).unwrap();
+    assert_eq!(
+        info.unresolved_symbols,
+        ["validate_expected", "validate_arm_only"]
+    );
+}
+
+#[test]
+fn test_max_depth() {
+    let tree = TempTree::new("max-depth");
+    for (lib, next) in [
+        ("libdepth_1.so", Some("libdepth_2.so")),
+        ("libdepth_2.so", Some("libdepth_3.so")),
+        ("libdepth_3.so", Some("libdepth_4.so")),
+        ("libdepth_4.so", None),
+        ("libdepth_side.so", Some("libdepth_4.so")),
+    ] {
+        let mut elf = ElfFixture::lib().rpath("$ORIGIN");
+        if let Some(next) = next {
+            elf = elf.needed(next);
+        }
+        tree.write_elf(&format!("opt/{lib}"), &elf);
+    }
+    let bin = tree.write_elf(
+        "bin/app",
+        &ElfFixture::exe()
+            .needed("libdepth_1.so")
+            .needed("libdepth_side.so")
+            .rpath("$ORIGIN/../opt"),
+    );
+    let names = |max_depth| {
+        let options = RlddOptions {
+            max_depth,
+            ..Default::default()
+        };
+        let info = rldd_rex_with_options(&bin, options).unwrap();
+        let mut names: Vec<_> = info.deps.into_iter().map(|(name, _)| name).collect();
+        names.sort();
+        (names, info.warnings)
+    };
+
+    let depth_warnings = |warnings: &[String]| {
+        warnings
+            .iter()
+            .filter(|w| w.contains("max recursion depth"))
+            .count()
+    };
+
+    // both direct deps get cut, still a single warning
+    let (direct, warnings) = names(1);
+    assert_eq!(direct, ["libdepth_1.so", "libdepth_side.so"]);
+    assert_eq!(depth_warnings(&warnings), 1);
+
+    let (truncated, warnings) = names(3);
+    assert_eq!(depth_warnings(&warnings), 1);
+    // libdepth_4.so is cut off below libdepth_3.so but still reached through
+    // libdepth_side.so
+    assert_eq!(
+        truncated,
+        [
+            "libdepth_1.so",
+            "libdepth_2.so",
+            "libdepth_3.so",
+            "libdepth_4.so",
+            "libdepth_side.so",
+        ]
+    );
+
+    let (full, warnings) = names(RlddOptions::default().max_depth);
+    assert_eq!(full.len(), 5);
+    assert!(warnings.is_empty());
+}
@@ -0,0 +1,516 @@
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use goblin::elf::dynamic::*;
+use goblin::elf::header::*;
+use goblin::elf::program_header::*;
+use goblin::elf::section_header::*;
+
+static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+pub struct TempTree(PathBuf);
+
+impl TempTree {
+    pub fn new(tag: &str) -> Self {
+        let n = COUNTER.fetch_add(1, Ordering::SeqCst);
+        let dir =
+            std::env::temp_dir().join(format!("rldd-rex-{}-{}-{}", tag, std::process::id(), n));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        TempTree(dir.canonicalize().unwrap())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    pub fn join(&self, rel: &str) -> PathBuf {
+        self.0.join(rel)
+    }
+
+    pub fn mkdir(&self, rel: &str) -> PathBuf {
+        let dir = self.join(rel);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    pub fn write(&self, rel: &str, data: &[u8]) -> PathBuf {
+        let path = self.join(rel);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+        fs::write(&path, data).unwrap();
+        path
+    }
+
+    pub fn write_elf(&self, rel: &str, elf: &ElfFixture) -> PathBuf {
+        self.write(rel, &elf.bytes())
+    }
+}
+
+impl Drop for TempTree {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Minimal ELF writer: everything lives in a single PT_LOAD mapped at
+/// vaddr 0, so virtual addresses and file offsets are the same.
+#[derive(Clone)]
+pub struct ElfFixture {
+    pub is_64: bool,
+    pub big_endian: bool,
+    pub e_type: u16,
+    pub machine: u16,
+    pub flags: u32,
+    pub interp: Option<String>,
+    pub needed: Vec<String>,
+    pub rpath: Vec<String>,
+    pub runpath: Vec<String>,
+    pub soname: Option<String>,
+    pub dynamic: bool,
+    // (name, defined)
+    pub symbols: Vec<(String, bool)>,
+    // DT_GNU_HASH instead of DT_HASH for the symbol table
+    pub gnu_hash: bool,
+    pub dyn_entries: Vec<(u64, u64)>,
+    // (p_type, p_flags, p_memsz)
+    pub phdrs: Vec<(u32, u32, u64)>,
+    pub sections: Vec<Section>,
+}
+
+#[derive(Clone)]
+pub struct Section {
+    pub name: String,
+    pub sh_type: u32,
+    pub data: Vec<u8>,
+    pub link: u32,
+    pub entsize: u64,
+}
+
+impl ElfFixture {
+    pub fn lib() -> Self {
+        ElfFixture {
+            is_64: true,
+            big_endian: false,
+            e_type: ET_DYN,
+            machine: EM_X86_64,
+            flags: 0,
+            interp: None,
+            needed: Vec::new(),
+            rpath: Vec::new(),
+            runpath: Vec::new(),
+            soname: None,
+            dynamic: true,
+            symbols: Vec::new(),
+            gnu_hash: false,
+            dyn_entries: Vec::new(),
+            phdrs: vec![(PT_GNU_STACK, PF_R | PF_W, 0)],
+            sections: Vec::new(),
+        }
+    }
+
+    // No PT_INTERP by default: the host's loader would show up in every
+    // result. Set `interp` for tests about it.
+    pub fn exe() -> Self {
+        ElfFixture {
+            e_type: ET_EXEC,
+            ..Self::lib()
+        }
+    }
+
+    pub fn object() -> Self {
+        ElfFixture {
+            e_type: ET_REL,
+            dynamic: false,
+            ..Self::lib()
+        }
+    }
+
+    pub fn elf32(mut self) -> Self {
+        self.is_64 = false;
+        self.machine = EM_386;
+        self
+    }
+
+    pub fn machine(mut self, machine: u16) -> Self {
+        self.machine = machine;
+        self
+    }
+
+    pub fn needed(mut self, lib: &str) -> Self {
+        self.needed.push(lib.into());
+        self
+    }
+
+    pub fn rpath(mut self, dir: &str) -> Self {
+        self.rpath.push(dir.into());
+        self
+    }
+
+    pub fn runpath(mut self, dir: &str) -> Self {
+        self.runpath.push(dir.into());
+        self
+    }
+
+    pub fn soname(mut self, name: &str) -> Self {
+        self.soname = Some(name.into());
+        self
+    }
+
+    pub fn import(mut self, sym: &str) -> Self {
+        self.symbols.push((sym.into(), false));
+        self
+    }
+
+    pub fn export(mut self, sym: &str) -> Self {
+        self.symbols.push((sym.into(), true));
+        self
+    }
+
+    pub fn gnu_hash(mut self) -> Self {
+        self.gnu_hash = true;
+        self
+    }
+
+    pub fn dyn_entry(mut self, tag: u64, val: u64) -> Self {
+        self.dyn_entries.push((tag, val));
+        self
+    }
+
+    pub fn exec_stack(mut self) -> Self {
+        for ph in self.phdrs.iter_mut().filter(|ph| ph.0 == PT_GNU_STACK) {
+            ph.1 |= PF_X;
+        }
+        self
+    }
+
+    pub fn phdr(mut self, p_type: u32, p_flags: u32, p_memsz: u64) -> Self {
+        self.phdrs.push((p_type, p_flags, p_memsz));
+        self
+    }
+
+    pub fn section(mut self, name: &str, sh_type: u32, data: &[u8]) -> Self {
+        self.sections.push(Section {
+            name: name.into(),
+            sh_type,
+            data: data.to_vec(),
+            link: 0,
+            entsize: 0,
+        });
+        self
+    }
+
+    pub fn bytes(&self) -> Vec<u8> {
+        let w = Writer {
+            is_64: self.is_64,
+            big_endian: self.big_endian,
+        };
+        let ehsize = if self.is_64 { 64 } else { 52 };
+        let phentsize = if self.is_64 { 56 } else { 32 };
+        let dynentsize = if self.is_64 { 16 } else { 8 };
+
+        let mut strtab = vec![0u8];
+        let mut add_str = |s: &str| {
+            let off = strtab.len() as u64;
+            strtab.extend_from_slice(s.as_bytes());
+            strtab.push(0);
+            off
+        };
+
+        let mut dyns = Vec::new();
+        for lib in &self.needed {
+            dyns.push((DT_NEEDED, add_str(lib)));
+        }
+        if let Some(soname) = &self.soname {
+            dyns.push((DT_SONAME, add_str(soname)));
+        }
+        if !self.rpath.is_empty() {
+            dyns.push((DT_RPATH, add_str(&self.rpath.join(":"))));
+        }
+        if !self.runpath.is_empty() {
+            dyns.push((DT_RUNPATH, add_str(&self.runpath.join(":"))));
+        }
+        let sym_names: Vec<_> = self.symbols.iter().map(|(n, _)| add_str(n)).collect();
+        let mut syms = Vec::new();
+        w.sym(&mut syms, 0, 0, 0);
+        for ((_, defined), name) in self.symbols.iter().zip(&sym_names) {
+            let (shndx, value) = if *defined { (1, 0x1000) } else { (0, 0) };
+            w.sym(&mut syms, *name as u32, shndx, value);
+        }
+        let syment = if self.is_64 { 24 } else { 16 };
+        dyns.extend_from_slice(&self.dyn_entries);
+
+        // (p_type, p_flags, offset, size)
+        let mut phdrs: Vec<(u32, u32, u64, u64)> = Vec::new();
+        let phnum = 1 + self.interp.is_some() as usize + self.dynamic as usize + self.phdrs.len();
+        let base = ehsize + phnum * phentsize;
+        let mut body = Vec::new();
+        let align = |body: &mut Vec<u8>, n: usize| {
+            while !(base + body.len()).is_multiple_of(n) {
+                body.push(0);
+            }
+        };
+
+        if let Some(interp) = &self.interp {
+            phdrs.push((PT_INTERP, PF_R, base as u64, interp.len() as u64 + 1));
+            body.extend_from_slice(interp.as_bytes());
+            body.push(0);
+        }
+
+        if self.dynamic {
+            let strtab_off = (base + body.len()) as u64;
+            body.extend_from_slice(&strtab);
+            dyns.push((DT_STRTAB, strtab_off));
+            dyns.push((DT_STRSZ, strtab.len() as u64));
+
+            if !self.symbols.is_empty() {
+                align(&mut body, 8);
+                let symtab_off = (base + body.len()) as u64;
+                let nsyms = self.symbols.len() + 1;
+                body.extend_from_slice(&syms);
+
+                align(&mut body, 8);
+                let hash_off = (base + body.len()) as u64;
+                if self.gnu_hash {
+                    // one bucket holding every symbol after the null one,
+                    // the last chain entry has the end bit set
+                    for v in [1, 1, 1, 0] {
+                        w.u32(&mut body, v);
+                    }
+                    w.word(&mut body, 0);
+                    w.u32(&mut body, 1);
+                    for i in 1..nsyms {
+                        w.u32(&mut body, (i == nsyms - 1) as u32);
+                    }
+                } else {
+                    for v in [1, nsyms as u32, 0] {
+                        w.u32(&mut body, v);
+                    }
+                    for _ in 0..nsyms {
+                        w.u32(&mut body, 0);
+                    }
+                }
+
+                dyns.push((DT_SYMTAB, symtab_off));
+                dyns.push((DT_SYMENT, syment));
+                dyns.push((if self.gnu_hash { DT_GNU_HASH } else { DT_HASH }, hash_off));
+            }
+
+            dyns.push((DT_NULL, 0));
+            align(&mut body, 8);
+            phdrs.push((
+                PT_DYNAMIC,
+                PF_R | PF_W,
+                (base + body.len()) as u64,
+                (dyns.len() * dynentsize) as u64,
+            ));
+            for (tag, val) in &dyns {
+                w.word(&mut body, *tag);
+                w.word(&mut body, *val);
+            }
+        }
+
+        for (p_type, p_flags, p_memsz) in &self.phdrs {
+            phdrs.push((*p_type, *p_flags, 0, *p_memsz));
+        }
+
+        let total = (base + body.len()) as u64;
+        phdrs.insert(0, (PT_LOAD, PF_R | PF_X, 0, total));
+
+        // section contents go after the loaded image, headers at the very end
+        let mut sections = self.sections.clone();
+        if !self.dynamic && !self.symbols.is_empty() {
+            // relocatable objects only carry the static symbol table
+            sections.push(Section {
+                name: ".strtab".into(),
+                sh_type: SHT_STRTAB,
+                data: strtab.clone(),
+                link: 0,
+                entsize: 0,
+            });
+            sections.push(Section {
+                name: ".symtab".into(),
+                sh_type: SHT_SYMTAB,
+                data: syms,
+                link: sections.len() as u32,
+                entsize: syment,
+            });
+        }
+
+        let (mut shoff, mut shnum, mut shstrndx) = (0, 0, 0);
+        if !sections.is_empty() {
+            let mut shstrtab = vec![0u8];
+            let mut offsets = Vec::new();
+            let mut names = Vec::new();
+            for s in &sections {
+                names.push(shstrtab.len() as u32);
+                shstrtab.extend_from_slice(s.name.as_bytes());
+                shstrtab.push(0);
+                align(&mut body, 8);
+                offsets.push((base + body.len()) as u64);
+                body.extend_from_slice(&s.data);
+            }
+            let shstrtab_name = shstrtab.len() as u32;
+            shstrtab.extend_from_slice(b".shstrtab\0");
+            let shstrtab_off = (base + body.len()) as u64;
+            body.extend_from_slice(&shstrtab);
+
+            align(&mut body, 8);
+            shoff = (base + body.len()) as u64;
+            shnum = sections.len() as u16 + 2;
+            shstrndx = shnum - 1;
+            let mut headers = Vec::new();
+            w.shdr(&mut headers, 0, SHT_NULL, 0, 0, 0, 0);
+            for ((s, off), name) in sections.iter().zip(offsets).zip(names) {
+                let size = s.data.len() as u64;
+                w.shdr(&mut headers, name, s.sh_type, off, size, s.link, s.entsize);
+            }
+            let size = shstrtab.len() as u64;
+            w.shdr(
+                &mut headers,
+                shstrtab_name,
+                SHT_STRTAB,
+                shstrtab_off,
+                size,
+                0,
+                0,
+            );
+            body.extend_from_slice(&headers);
+        }
+        let shentsize = if shnum == 0 {
+            0
+        } else if self.is_64 {
+            64
+        } else {
+            40
+        };
+
+        let mut out = Vec::new();
+        out.extend_from_slice(b"\x7fELF");
+        out.push(if self.is_64 { ELFCLASS64 } else { ELFCLASS32 });
+        out.push(if self.big_endian {
+            ELFDATA2MSB
+        } else {
+            ELFDATA2LSB
+        });
+        out.push(EV_CURRENT);
+        out.resize(16, 0);
+        w.u16(&mut out, self.e_type);
+        w.u16(&mut out, self.machine);
+        w.u32(&mut out, EV_CURRENT as u32);
+        w.word(&mut out, 0); // e_entry
+        w.word(&mut out, ehsize as u64); // e_phoff
+        w.word(&mut out, shoff);
+        w.u32(&mut out, self.flags);
+        w.u16(&mut out, ehsize as u16);
+        w.u16(&mut out, phentsize as u16);
+        w.u16(&mut out, phnum as u16);
+        w.u16(&mut out, shentsize);
+        w.u16(&mut out, shnum);
+        w.u16(&mut out, shstrndx);
+
+        for (p_type, p_flags, p_off, p_size) in phdrs {
+            w.u32(&mut out, p_type);
+            if self.is_64 {
+                w.u32(&mut out, p_flags);
+            }
+            w.word(&mut out, p_off);
+            w.word(&mut out, p_off);
+            w.word(&mut out, p_off);
+            w.word(
+                &mut out,
+                if p_off == 0 && p_type != PT_LOAD {
+                    0
+                } else {
+                    p_size
+                },
+            );
+            w.word(&mut out, p_size);
+            if !self.is_64 {
+                w.u32(&mut out, p_flags);
+            }
+            w.word(&mut out, if p_type == PT_LOAD { 0x1000 } else { 8 });
+        }
+
+        out.extend_from_slice(&body);
+        out
+    }
+}
+
+struct Writer {
+    is_64: bool,
+    big_endian: bool,
+}
+
+impl Writer {
+    fn u16(&self, out: &mut Vec<u8>, v: u16) {
+        if self.big_endian {
+            out.extend_from_slice(&v.to_be_bytes());
+        } else {
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+    }
+
+    fn u32(&self, out: &mut Vec<u8>, v: u32) {
+        if self.big_endian {
+            out.extend_from_slice(&v.to_be_bytes());
+        } else {
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+    }
+
+    fn sym(&self, out: &mut Vec<u8>, name: u32, shndx: u16, value: u64) {
+        // STB_GLOBAL, STT_FUNC
+        let info = (1 << 4) | 2;
+        self.u32(out, name);
+        if self.is_64 {
+            out.push(info);
+            out.push(0);
+            self.u16(out, shndx);
+            self.word(out, value);
+            self.word(out, 0);
+        } else {
+            self.word(out, value);
+            self.word(out, 0);
+            out.push(info);
+            out.push(0);
+            self.u16(out, shndx);
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn shdr(
+        &self,
+        out: &mut Vec<u8>,
+        name: u32,
+        sh_type: u32,
+        offset: u64,
+        size: u64,
+        link: u32,
+        entsize: u64,
+    ) {
+        self.u32(out, name);
+        self.u32(out, sh_type);
+        self.word(out, 0); // sh_flags
+        self.word(out, 0); // sh_addr
+        self.word(out, offset);
+        self.word(out, size);
+        self.u32(out, link);
+        self.u32(out, 0); // sh_info
+        self.word(out, 1); // sh_addralign
+        self.word(out, entsize);
+    }
+
+    fn word(&self, out: &mut Vec<u8>, v: u64) {
+        match (self.is_64, self.big_endian) {
+            (true, true) => out.extend_from_slice(&v.to_be_bytes()),
+            (true, false) => out.extend_from_slice(&v.to_le_bytes()),
+            (false, true) => out.extend_from_slice(&(v as u32).to_be_bytes()),
+            (false, false) => out.extend_from_slice(&(v as u32).to_le_bytes()),
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use crate::{ResolvedLib, Resolver};
+use std::collections::HashMap;
+use std::path::Path;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mark {
+    Visiting,
+    Done,
+}
+
+impl Resolver {
+    // Resolved closure with every library after all of its own dependencies.
+    // Cycles are broken at the edge that closes them, following DT_NEEDED
+    // order, so the result is the same on every run.
+    pub fn topo_sorted_closure<P: AsRef<Path> + std::fmt::Debug>(
+        &self,
+        path: P,
+    ) -> Vec<ResolvedLib> {
+        let Ok(graph) = self.closure_graph(path.as_ref()) else {
+            return Vec::new();
+        };
+
+        let mut marks: HashMap<&Path, Mark> = HashMap::new();
+        let mut order = Vec::new();
+        // (library, index of the next DT_NEEDED entry to visit, its needed list)
+        let mut stack: Vec<(&ResolvedLib, usize, Vec<String>)> = Vec::new();
+
+        for name in &graph.needed {
+            let Some(start) = graph.resolved(name) else {
+                continue;
+            };
+            let start_path = start.path.as_deref().unwrap();
+            if marks.contains_key(start_path) {
+                continue;
+            }
+            marks.insert(start_path, Mark::Visiting);
+            stack.push((start, 0, self.needed_of(start_path)));
+
+            while let Some((lib, next, needed)) = stack.last_mut() {
+                let lib: &ResolvedLib = lib;
+                let Some(child_name) = needed.get(*next) else {
+                    marks.insert(lib.path.as_deref().unwrap(), Mark::Done);
+                    order.push(lib.clone());
+                    stack.pop();
+                    continue;
+                };
+                *next += 1;
+                let Some(child) = graph.resolved(child_name) else {
+                    continue;
+                };
+                let child_path = child.path.as_deref().unwrap();
+                match marks.get(child_path) {
+                    Some(Mark::Done) => {}
+                    Some(Mark::Visiting) => {
+                        self.warn(format!(
+                            "Warning: dependency cycle {} -> {}, ordering {} first",
+                            lib.name, child.name, lib.name
+                        ));
+                    }
+                    None => {
+                        marks.insert(child_path, Mark::Visiting);
+                        stack.push((child, 0, self.needed_of(child_path)));
+                    }
+                }
+            }
+        }
+        order
+    }
+
+    fn needed_of(&self, path: &Path) -> Vec<String> {
+        self.object(path)
+            .map(|obj| obj.libraries.clone())
+            .unwrap_or_default()
+    }
+}
+
+pub fn topo_sorted_closure<P: AsRef<Path> + std::fmt::Debug>(path: P) -> Vec<ResolvedLib> {
+    Resolver::new().topo_sorted_closure(path)
+}
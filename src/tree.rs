@@ -0,0 +1,84 @@
+use crate::{ClosureGraph, DepStatus, Resolver};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DepNode {
+    pub name: String,
+    pub status: DepStatus,
+    pub path: Option<PathBuf>,
+    pub children: Vec<DepNode>,
+    // already expanded earlier in the tree, children left out
+    pub back_ref: bool,
+}
+
+impl Resolver {
+    // The closure as a tree, each library under the object whose DT_NEEDED
+    // asked for it. Resolutions are the ones rldd_rex reports.
+    pub fn rldd_rex_tree<P: AsRef<Path> + std::fmt::Debug>(&self, path: P) -> DepNode {
+        let root = self.root_path(path.as_ref());
+        let name = root.file_name().map_or_else(
+            || root.display().to_string(),
+            |n| n.to_string_lossy().into_owned(),
+        );
+        let mut node = DepNode {
+            name,
+            status: DepStatus::NotFound,
+            path: None,
+            children: Vec::new(),
+            back_ref: false,
+        };
+        let Ok(graph) = self.closure_graph(path.as_ref()) else {
+            return node;
+        };
+
+        let mut expanded = HashSet::new();
+        expanded.insert(graph.root.clone());
+        node.children = graph
+            .needed
+            .iter()
+            .map(|dep| self.tree_node(dep, &graph, &mut expanded))
+            .collect();
+        node.status = DepStatus::Found(graph.root.clone());
+        node.path = Some(graph.root);
+        node
+    }
+
+    fn tree_node(
+        &self,
+        name: &str,
+        graph: &ClosureGraph,
+        expanded: &mut HashSet<PathBuf>,
+    ) -> DepNode {
+        let (path, status) = match graph.libs.get(name) {
+            Some((lib, status)) => (lib.path.clone(), status.clone()),
+            None => (None, DepStatus::NotFound),
+        };
+        let mut node = DepNode {
+            name: name.to_string(),
+            status,
+            path,
+            children: Vec::new(),
+            back_ref: false,
+        };
+        let Some(path) = node.path.clone().filter(|_| node.status.is_resolved()) else {
+            return node;
+        };
+        if !expanded.insert(path.clone()) {
+            node.back_ref = true;
+            return node;
+        }
+        if let Ok(obj) = self.object(&path) {
+            node.children = obj
+                .libraries
+                .iter()
+                .map(|dep| self.tree_node(dep, graph, expanded))
+                .collect();
+        }
+        node
+    }
+}
+
+pub fn rldd_rex_tree<P: AsRef<Path> + std::fmt::Debug>(path: P) -> DepNode {
+    Resolver::new().rldd_rex_tree(path)
+}
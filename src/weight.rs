@@ -0,0 +1,80 @@
+use crate::Resolver;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WeightStats {
+    pub libs: usize,
+    pub bytes: u64,
+}
+
+pub const SHARED_BUCKET: &str = "shared";
+
+impl Resolver {
+    // Per direct dependency, the unique libraries only it pulls in. Anything
+    // reachable from two or more direct deps goes to the "shared" bucket.
+    pub fn weight_report<P: AsRef<Path> + std::fmt::Debug>(
+        &self,
+        path: P,
+    ) -> Vec<(String, WeightStats)> {
+        let Ok(graph) = self.closure_graph(path.as_ref()) else {
+            return Vec::new();
+        };
+
+        let reachable = |name: &str| {
+            let mut seen = HashSet::new();
+            let mut stack = vec![name.to_string()];
+            while let Some(name) = stack.pop() {
+                let Some(path) = graph.resolved(&name).and_then(|lib| lib.path.as_deref()) else {
+                    continue;
+                };
+                if !seen.insert(path) {
+                    continue;
+                }
+                if let Ok(obj) = self.object(path) {
+                    stack.extend(obj.libraries.iter().rev().cloned());
+                }
+            }
+            seen
+        };
+
+        let mut direct: Vec<&str> = Vec::new();
+        for name in &graph.needed {
+            if !direct.contains(&name.as_str()) {
+                direct.push(name);
+            }
+        }
+        let closures: Vec<_> = direct.iter().map(|name| reachable(name)).collect();
+
+        let mut owners: HashMap<&Path, Vec<usize>> = HashMap::new();
+        for (i, closure) in closures.iter().enumerate() {
+            for &path in closure {
+                owners.entry(path).or_default().push(i);
+            }
+        }
+
+        let mut report: Vec<(String, WeightStats)> = direct
+            .iter()
+            .map(|name| (name.to_string(), WeightStats::default()))
+            .collect();
+        let mut shared = WeightStats::default();
+        for (path, owners) in owners {
+            let stats = match owners[..] {
+                [only] => &mut report[only].1,
+                _ => &mut shared,
+            };
+            stats.libs += 1;
+            stats.bytes += fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        }
+
+        if shared.libs > 0 {
+            report.push((SHARED_BUCKET.to_string(), shared));
+        }
+        report
+    }
+}
+
+pub fn weight_report<P: AsRef<Path> + std::fmt::Debug>(path: P) -> Vec<(String, WeightStats)> {
+    Resolver::new().weight_report(path)
+}